@@ -1,4 +1,5 @@
 use crate::validate::Dialect;
+use std::collections::HashMap;
 
 pub struct ParameterInfo {
     pub allow_plural: bool,
@@ -12,10 +13,26 @@ pub enum Occurence {
     EXACT,   //< Command must match exactly with base.
 }
 
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Category {
+    CHARACTER, //< Command inserts a (special) character.
+    FONT,      //< Command changes the font size.
+    COLOUR,    //< Command changes the text colour.
+    PARAMETER, //< Command formats a parameter.
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ParamKind {
+    NUMERIC,  //< Parameter is a number.
+    STRING,   //< Parameter is another string.
+    CURRENCY, //< Parameter is an amount of money.
+}
+
 pub struct CommandInfo<'a> {
     pub name: &'a str,
     pub norm_name: Option<&'a str>,
     pub dialects: &'a [Dialect],
+    pub category: Category,
     pub occurence: Occurence,
     pub allow_case: bool,
     pub def_plural_subindex: Option<usize>,
@@ -26,6 +43,66 @@ impl<'a> CommandInfo<'a> {
     pub fn get_norm_name(&self) -> &'a str {
         self.norm_name.unwrap_or(self.name)
     }
+
+    /// Number of parameters consumed by the command.
+    /// Each command takes a single position; its parameters are addressed via subindices.
+    pub fn arity(&self) -> usize {
+        self.parameters.len()
+    }
+}
+
+/// Source of metadata about string commands.
+/// The builtin COMMANDS table implements this, but users can supply their own.
+pub trait CommandMeta {
+    /// Metadata of a command, if the command exists in the dialect.
+    fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>>;
+    /// Whether a command with this name exists in the dialect.
+    fn is_known(&self, dialect: &Dialect, name: &str) -> bool {
+        self.get(dialect, name).is_some()
+    }
+    /// Number of parameters consumed by the command; zero for unknown commands.
+    /// The command still takes a single position; its parameters are addressed via subindices.
+    fn arity(&self, dialect: &Dialect, name: &str) -> usize {
+        self.get(dialect, name).map_or(0, |ci| ci.arity())
+    }
+    /// Kind of parameter the command formats, if known. See infer_param_kind.
+    fn param_type(&self, dialect: &Dialect, name: &str) -> Option<ParamKind> {
+        self.get(dialect, name)
+            .filter(|ci| ci.category == Category::PARAMETER)
+            .and_then(|ci| infer_param_kind(ci.get_norm_name()))
+    }
+    /// Whether the command allows a case selection.
+    fn allow_case(&self, dialect: &Dialect, name: &str) -> bool {
+        self.get(dialect, name).is_some_and(|ci| ci.allow_case)
+    }
+    /// Maximum number of times the command may appear in a single string, if limited.
+    /// Only custom registries set limits; the builtin COMMANDS have none.
+    fn max_occurrences(&self, _name: &str) -> Option<usize> {
+        None
+    }
+}
+
+impl<'a> CommandMeta for [CommandInfo<'a>] {
+    fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+        self.iter()
+            .find(|ci| ci.name == name && ci.dialects.contains(dialect))
+    }
+}
+
+impl<T: CommandMeta + ?Sized> CommandMeta for &T {
+    fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+        (**self).get(dialect, name)
+    }
+
+    fn max_occurrences(&self, name: &str) -> Option<usize> {
+        (**self).max_occurrences(name)
+    }
+}
+
+impl<'a> CommandMeta for HashMap<String, CommandInfo<'a>> {
+    fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+        HashMap::get(self, name).filter(|ci| ci.dialects.contains(dialect))
+    }
 }
 
 const P__: ParameterInfo = ParameterInfo {
@@ -45,17 +122,40 @@ const PPG: ParameterInfo = ParameterInfo {
     allow_gender: true,
 };
 
-const DN__: &'static [Dialect] = &[Dialect::NEWGRF];
-const DNGO: &'static [Dialect] = &[Dialect::NEWGRF, Dialect::GAMESCRIPT, Dialect::OPENTTD];
-const D_GO: &'static [Dialect] = &[Dialect::GAMESCRIPT, Dialect::OPENTTD];
-const D__O: &'static [Dialect] = &[Dialect::OPENTTD];
+const DN__: &[Dialect] = &[Dialect::NEWGRF];
+const DNGO: &[Dialect] = &[Dialect::NEWGRF, Dialect::GAMESCRIPT, Dialect::OPENTTD];
+const D_GO: &[Dialect] = &[Dialect::GAMESCRIPT, Dialect::OPENTTD];
+const D__O: &[Dialect] = &[Dialect::OPENTTD];
+
+/// Name prefixes of commands, and the kind of parameter they format.
+pub const KIND_PREFIXES: &[(&str, ParamKind)] = &[
+    ("NUM", ParamKind::NUMERIC),
+    ("COMMA", ParamKind::NUMERIC),
+    ("DECIMAL", ParamKind::NUMERIC),
+    ("ZEROFILL_NUM", ParamKind::NUMERIC),
+    ("HEX", ParamKind::NUMERIC),
+    ("STRING", ParamKind::STRING),
+    ("RAW_STRING", ParamKind::STRING),
+    ("CURRENCY", ParamKind::CURRENCY),
+];
+
+/// Guess the kind of parameter a command formats, from its name alone.
+/// This works without any command metadata, but only for the prefixes in KIND_PREFIXES;
+/// commands like '{CARGO_LONG}' or '{DATE_LONG}' are not recognized.
+pub fn infer_param_kind(name: &str) -> Option<ParamKind> {
+    KIND_PREFIXES
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, kind)| *kind)
+}
 
-pub const COMMANDS: &'static [CommandInfo] = &[
+pub const COMMANDS: &[CommandInfo] = &[
     // names for unicode characters, freely usable by translators
     CommandInfo {
         name: "NBSP",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -65,6 +165,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "{",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -75,6 +176,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LRM",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -84,6 +186,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RLM",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -93,6 +196,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LRE",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -102,6 +206,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RLE",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -111,6 +216,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LRO",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -120,6 +226,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RLO",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -129,6 +236,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "PDF",
         norm_name: None,
         dialects: D_GO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -139,6 +247,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -148,6 +257,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "COPYRIGHT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -157,6 +267,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "TRAIN",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -166,6 +277,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LORRY",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -175,6 +287,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BUS",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -184,6 +297,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "PLANE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -193,6 +307,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SHIP",
         norm_name: None,
         dialects: DNGO,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -205,6 +320,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "REV",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -214,6 +330,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UP_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -223,6 +340,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SMALL_UP_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -232,6 +350,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SMALL_DOWN_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -241,6 +360,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DOWN_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -250,6 +370,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CHECKMARK",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -259,6 +380,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CROSS",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -269,6 +391,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RIGHT_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -278,6 +401,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SMALL_LEFT_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -287,6 +411,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SMALL_RIGHT_ARROW",
         norm_name: None,
         dialects: D__O,
+        category: Category::CHARACTER,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -296,6 +421,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STATION_FEATURES",
         norm_name: None,
         dialects: D__O,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -306,6 +432,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "NORMAL_FONT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::FONT,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -315,6 +442,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "TINY_FONT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::FONT,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -324,6 +452,27 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BIG_FONT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::FONT,
+        occurence: Occurence::EXACT,
+        allow_case: false,
+        def_plural_subindex: None,
+        parameters: &[],
+    },
+    CommandInfo {
+        name: "TINYFONT",
+        norm_name: Some("TINY_FONT"),
+        dialects: DN__,
+        category: Category::FONT,
+        occurence: Occurence::EXACT,
+        allow_case: false,
+        def_plural_subindex: None,
+        parameters: &[],
+    },
+    CommandInfo {
+        name: "BIGFONT",
+        norm_name: Some("BIG_FONT"),
+        dialects: DN__,
+        category: Category::FONT,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -333,6 +482,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "MONO_FONT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::FONT,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -343,6 +493,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BLUE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -352,6 +503,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SILVER",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -361,6 +513,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "GOLD",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -370,6 +523,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RED",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -379,6 +533,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "PURPLE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -388,6 +543,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LTBROWN",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -397,6 +553,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "ORANGE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -406,6 +563,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "GREEN",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -415,6 +573,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "YELLOW",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -424,6 +583,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DKGREEN",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -433,6 +593,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CREAM",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -442,6 +603,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BROWN",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -451,6 +613,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "WHITE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -460,6 +623,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "LTBLUE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -469,6 +633,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "GRAY",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -478,6 +643,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DKBLUE",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -487,6 +653,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BLACK",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -497,6 +664,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "PUSH_COLOUR",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -506,6 +674,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "POP_COLOUR",
         norm_name: None,
         dialects: DNGO,
+        category: Category::COLOUR,
         occurence: Occurence::ANY,
         allow_case: false,
         def_plural_subindex: None,
@@ -515,6 +684,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "COLOUR",
         norm_name: None,
         dialects: D_GO,
+        category: Category::COLOUR,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -524,16 +694,29 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "POP_WORD",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
+    // skips a parameter without output; translations may use position references instead
+    CommandInfo {
+        name: "SKIP",
+        norm_name: None,
+        dialects: D_GO,
+        category: Category::PARAMETER,
+        occurence: Occurence::ANY,
+        allow_case: false,
+        def_plural_subindex: None,
+        parameters: &[P__],
+    },
     // substrings
     CommandInfo {
         name: "STRING",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -543,6 +726,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "RAW_STRING",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -552,6 +736,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING1",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -561,6 +746,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING2",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -570,6 +756,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING3",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -579,6 +766,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING4",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -588,6 +776,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING5",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -597,6 +786,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING6",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -606,6 +796,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STRING7",
         norm_name: Some("STRING"),
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -616,6 +807,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "COMMA",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -625,6 +817,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SIGNED_WORD",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -634,6 +827,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UNSIGNED_WORD",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -643,6 +837,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "HEX",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -652,6 +847,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "NUM",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -662,6 +858,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "ZEROFILL_NUM",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -671,6 +868,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DECIMAL",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -681,6 +879,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "BYTES",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -690,6 +889,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "HEIGHT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -699,6 +899,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CURRENCY",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -708,6 +909,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CURRENCY_LONG",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -717,6 +919,17 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CURRENCY_SHORT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
+        occurence: Occurence::EXACT,
+        allow_case: false,
+        def_plural_subindex: Some(0),
+        parameters: &[PP_],
+    },
+    CommandInfo {
+        name: "CURRCOMPACT",
+        norm_name: Some("CURRENCY_SHORT"),
+        dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -726,6 +939,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "VELOCITY",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -735,6 +949,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "VOLUME",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -744,6 +959,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "VOLUME_LONG",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -753,6 +969,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "VOLUME_SHORT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -762,6 +979,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "FORCE",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -771,6 +989,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "POWER",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -780,6 +999,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "POWER_TO_WEIGHT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -789,6 +1009,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "WEIGHT",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -798,6 +1019,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "WEIGHT_LONG",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -807,6 +1029,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "WEIGHT_SHORT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -816,6 +1039,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UNITS_DAYS_OR_SECONDS",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -825,6 +1049,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UNITS_MONTHS_OR_MINUTES",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -834,6 +1059,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UNITS_YEARS_OR_PERIODS",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -843,6 +1069,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "UNITS_YEARS_OR_MINUTES",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(0),
@@ -853,6 +1080,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CARGO_LONG",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(1),
@@ -862,6 +1090,27 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CARGO_SHORT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
+        occurence: Occurence::EXACT,
+        allow_case: false,
+        def_plural_subindex: Some(1),
+        parameters: &[P_G, PP_],
+    },
+    CommandInfo {
+        name: "CARGO",
+        norm_name: Some("CARGO_LONG"),
+        dialects: DNGO,
+        category: Category::PARAMETER,
+        occurence: Occurence::EXACT,
+        allow_case: false,
+        def_plural_subindex: Some(1),
+        parameters: &[P_G, PP_],
+    },
+    CommandInfo {
+        name: "SHORTCARGO",
+        norm_name: Some("CARGO_SHORT"),
+        dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(1),
@@ -871,6 +1120,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CARGO_TINY",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: Some(1),
@@ -881,6 +1131,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE1920_LONG",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: true,
         def_plural_subindex: None,
@@ -890,6 +1141,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE1920_SHORT",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: true,
         def_plural_subindex: None,
@@ -899,6 +1151,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE_LONG",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: true,
         def_plural_subindex: None,
@@ -908,6 +1161,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE_SHORT",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: true,
         def_plural_subindex: None,
@@ -917,6 +1171,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE_TINY",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -926,6 +1181,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DATE_ISO",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: false,
         def_plural_subindex: None,
@@ -936,6 +1192,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CARGO_NAME",
         norm_name: None,
         dialects: DN__,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -945,6 +1202,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "CARGO_LIST",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::EXACT,
         allow_case: true,
         def_plural_subindex: None,
@@ -954,6 +1212,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "INDUSTRY",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: true,
         def_plural_subindex: None,
@@ -963,6 +1222,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "WAYPOINT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -972,6 +1232,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "STATION",
         norm_name: None,
         dialects: DNGO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -981,6 +1242,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "DEPOT",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -990,6 +1252,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "TOWN",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -999,6 +1262,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "GROUP",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1008,6 +1272,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "SIGN",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1017,6 +1282,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "ENGINE",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1026,6 +1292,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "VEHICLE",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1035,6 +1302,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "COMPANY",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1044,6 +1312,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "COMPANY_NUM",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
@@ -1053,6 +1322,7 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         name: "PRESIDENT_NAME",
         norm_name: None,
         dialects: D_GO,
+        category: Category::PARAMETER,
         occurence: Occurence::NONZERO,
         allow_case: false,
         def_plural_subindex: None,
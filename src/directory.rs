@@ -0,0 +1,346 @@
+use crate::validate::{
+    validate_translation, FindingKind, LanguageConfig, Severity, ValidationError,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Source of language files, see validate_languages.
+/// DirectoryReader reads them from disk, but other sources can be supplied.
+pub trait LanguageReader {
+    /// Names of all language files.
+    fn list(&self) -> io::Result<Vec<String>>;
+    /// Content of a language file.
+    fn read(&self, name: &str) -> io::Result<String>;
+}
+
+/// Reads the '.txt' files in a directory.
+pub struct DirectoryReader {
+    path: PathBuf,
+}
+
+impl DirectoryReader {
+    pub fn new(path: &Path) -> DirectoryReader {
+        DirectoryReader {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl LanguageReader for DirectoryReader {
+    fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".txt") {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn read(&self, name: &str) -> io::Result<String> {
+        fs::read_to_string(self.path.join(name))
+    }
+}
+
+/// A string of a language file: its line, name, case and text.
+struct LanguageString<'a> {
+    line: usize, //< line number, starting at 1
+    name: &'a str,
+    case: &'a str,
+    text: &'a str,
+}
+
+/// Split a language file into its strings, like 'STR_NAME.case    :Text'.
+/// Empty lines and lines starting with '#' are skipped; '##' headers are read by language_config.
+///
+/// @returns The strings, and an error for each line which is not a string.
+fn parse_language_file(content: &str) -> (Vec<LanguageString<'_>>, Vec<ValidationError>) {
+    let mut strings = Vec::new();
+    let mut errors = Vec::new();
+    for (lineno, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((name, text)) if !name.trim().is_empty() => {
+                let (name, case) = name
+                    .trim()
+                    .split_once('.')
+                    .unwrap_or((name.trim(), "default"));
+                strings.push(LanguageString {
+                    line: lineno + 1,
+                    name,
+                    case,
+                    text,
+                });
+            }
+            _ => errors.push(ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidLine,
+                pos_begin: None,
+                pos_end: None,
+                message: format!(
+                    "Line {}: Invalid line, 'STR_NAME :text' expected.",
+                    lineno + 1
+                ),
+                suggestion: None,
+            }),
+        }
+    }
+    (strings, errors)
+}
+
+/// Configuration of a language file, from its '##isocode', '##plural', '##gender' and '##case' headers.
+/// A known isocode selects the builtin configuration, see LanguageConfig::for_language;
+/// otherwise `fallback` is used. The other headers override the selected configuration.
+/// Dialect and severities are always taken from `fallback`.
+fn language_config(content: &str, fallback: &LanguageConfig) -> LanguageConfig {
+    let mut headers = HashMap::new();
+    for line in content.lines() {
+        if let Some(header) = line.trim_end().strip_prefix("##") {
+            let (name, value) = header
+                .split_once(char::is_whitespace)
+                .unwrap_or((header, ""));
+            headers.insert(name, value.trim());
+        }
+    }
+    let mut config = headers
+        .get("isocode")
+        .and_then(|code| LanguageConfig::for_language(code))
+        .unwrap_or_else(|| fallback.clone());
+    config.dialect = fallback.dialect;
+    config.severities = fallback.severities.clone();
+    if let Some(count) = headers
+        .get("plural")
+        .and_then(|form| form.parse().ok())
+        .and_then(LanguageConfig::plural_count_of_form)
+    {
+        config.plural_count = count;
+    }
+    if let Some(genders) = headers.get("gender") {
+        config.genders = genders.split_whitespace().map(String::from).collect();
+    }
+    if let Some(cases) = headers.get("case") {
+        config.cases = cases.split_whitespace().map(String::from).collect();
+    }
+    config
+}
+
+/**
+ * Validate all language files of a source, against the base language.
+ *
+ * Messages of findings start with the line and the name of the string they belong to.
+ * Each file is validated with its own configuration, taken from its headers; see language_config.
+ * Lines of the base language which are not a string are reported under the name of the base language.
+ *
+ * @param reader The source of the language files.
+ * @param base_lang The name of the base language file, for example "english.txt".
+ * @param config The configuration of translations whose headers do not name a known language.
+ *
+ * @returns The findings of each language file, and the invalid lines of the base language; or an error if the base language cannot be read.
+ */
+pub fn validate_languages<R: LanguageReader + ?Sized>(
+    reader: &R,
+    base_lang: &str,
+    config: &LanguageConfig,
+) -> io::Result<BTreeMap<String, Vec<ValidationError>>> {
+    let base_content = reader.read(base_lang)?;
+    let (base_strings, base_errors) = parse_language_file(&base_content);
+    let base: HashMap<&str, &str> = base_strings
+        .iter()
+        .filter(|s| s.case == "default")
+        .map(|s| (s.name, s.text))
+        .collect();
+
+    let mut result = BTreeMap::new();
+    if !base_errors.is_empty() {
+        result.insert(String::from(base_lang), base_errors);
+    }
+    for file in reader.list()? {
+        if file == base_lang {
+            continue;
+        }
+        let content = match reader.read(&file) {
+            Ok(content) => content,
+            Err(err) => {
+                result.insert(
+                    file,
+                    vec![ValidationError {
+                        severity: Severity::Error,
+                        kind: FindingKind::UnreadableFile,
+                        pos_begin: None,
+                        pos_end: None,
+                        message: format!("Cannot read file: {}.", err),
+                        suggestion: None,
+                    }],
+                );
+                continue;
+            }
+        };
+        let config = language_config(&content, config);
+        let (strings, mut findings) = parse_language_file(&content);
+        for string in strings {
+            let Some(base_text) = base.get(string.name) else {
+                findings.push(ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::UnknownString,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: format!(
+                        "Line {}: {}: String is not in the base language.",
+                        string.line, string.name
+                    ),
+                    suggestion: Some(String::from("Remove this string.")),
+                });
+                continue;
+            };
+            let validation = validate_translation(&config, base_text, string.case, string.text);
+            findings.extend(validation.errors.into_iter().map(|err| ValidationError {
+                message: format!("Line {}: {}: {}", string.line, string.name, err.message),
+                ..err
+            }));
+        }
+        result.insert(file, findings);
+    }
+    Ok(result)
+}
+
+/**
+ * Validate all language files in a directory, against the base language.
+ *
+ * @param path The directory with the language files.
+ * @param base_lang The name of the base language file, for example "english.txt".
+ * @param config The configuration of translations whose headers do not name a known language.
+ *
+ * @returns The findings of each language file, see validate_languages.
+ */
+pub fn validate_directory(
+    path: &Path,
+    base_lang: &str,
+    config: &LanguageConfig,
+) -> io::Result<BTreeMap<String, Vec<ValidationError>>> {
+    validate_languages(&DirectoryReader::new(path), base_lang, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{Dialect, SeverityMap};
+
+    struct MemoryReader {
+        files: BTreeMap<&'static str, &'static str>,
+    }
+
+    impl LanguageReader for MemoryReader {
+        fn list(&self) -> io::Result<Vec<String>> {
+            let mut names: Vec<String> = self.files.keys().map(|n| String::from(*n)).collect();
+            names.push(String::from("missing.txt"));
+            Ok(names)
+        }
+
+        fn read(&self, name: &str) -> io::Result<String> {
+            self.files
+                .get(name)
+                .map(|c| String::from(*c))
+                .ok_or(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    #[test]
+    fn test_validate_languages() {
+        let reader = MemoryReader {
+            files: BTreeMap::from([
+                (
+                    "english.txt",
+                    "##name English\n\
+                     STR_TRAINS   :{NUM} train{P \"\" s}\n\
+                     STR_BROKEN\n\
+                     STR_STATION  :{STATION}\n",
+                ),
+                (
+                    "dutch.txt",
+                    "# comment\n\
+                     STR_TRAINS   :{NUM} trein{P \"\" en}\n\
+                     STR_STATION  :{STRING}\n\
+                     STR_UNKNOWN  :Text\n\
+                     broken line\n",
+                ),
+                (
+                    "polish.txt",
+                    "##isocode pl_PL\n\
+                     ##plural 7\n\
+                     ##gender m f n\n\
+                     STR_TRAINS   :{NUM} pociąg{P \"\" i ów}\n\
+                     STR_STATION  :{G=m}{STATION}\n",
+                ),
+                (
+                    "unknown.txt",
+                    "##isocode xx_XX\n\
+                     ##plural 1\n\
+                     STR_TRAINS   :{NUM} trains{P \"\" s}\n",
+                ),
+            ]),
+        };
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let result = validate_languages(&reader, "english.txt", &config).unwrap();
+        assert_eq!(
+            result.keys().collect::<Vec<_>>(),
+            vec![
+                "dutch.txt",
+                "english.txt",
+                "missing.txt",
+                "polish.txt",
+                "unknown.txt"
+            ]
+        );
+        assert_eq!(
+            result["english.txt"]
+                .iter()
+                .map(|f| f.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Line 3: Invalid line, 'STR_NAME :text' expected."]
+        );
+
+        let dutch: Vec<&str> = result["dutch.txt"]
+            .iter()
+            .map(|f| f.message.as_str())
+            .collect();
+        assert_eq!(
+            dutch,
+            vec![
+                "Line 5: Invalid line, 'STR_NAME :text' expected.",
+                "Line 3: STR_STATION: Expected '{0:STATION}', found '{STRING}'.",
+                "Line 3: STR_STATION: String command '{0:STATION}' is missing.",
+                "Line 4: STR_UNKNOWN: String is not in the base language.",
+            ]
+        );
+        assert_eq!(result["polish.txt"], vec![]);
+        assert_eq!(
+            result["unknown.txt"]
+                .iter()
+                .map(|f| f.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Line 3: STR_TRAINS: No plural choices allowed."]
+        );
+
+        assert_eq!(result["missing.txt"].len(), 1);
+        assert!(result["missing.txt"][0]
+            .message
+            .starts_with("Cannot read file: "));
+
+        assert!(validate_languages(&reader, "german.txt", &config).is_err());
+    }
+}
@@ -1,8 +1,13 @@
-use serde_wasm_bindgen;
 use wasm_bindgen::prelude::*;
 
-mod commands;
-mod parser;
+pub mod commands;
+/// Validation of whole language files. The directory helpers read from the filesystem,
+/// which WebAssembly does not have; use validate_languages with a LanguageReader there.
+pub mod directory;
+pub mod parser;
+pub mod render;
+pub mod sarif;
+pub mod stats;
 pub mod validate;
 
 #[wasm_bindgen]
@@ -1,8 +1,5 @@
 use clap::Parser;
-
-mod commands;
-mod parser;
-mod validate;
+use nile_library::validate;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -19,6 +16,8 @@ struct Args {
     genders: Vec<String>,
     #[clap(short, long, default_value_t = 2)]
     plural_count: usize,
+    #[clap(long)]
+    relaxed_genders: bool,
 }
 
 fn main() {
@@ -28,6 +27,8 @@ fn main() {
         cases: args.cases,
         genders: args.genders,
         plural_count: args.plural_count,
+        relaxed_genders: args.relaxed_genders,
+        severities: validate::SeverityMap::new(),
     };
 
     let result = match args.translation {
@@ -1,59 +1,183 @@
+use crate::commands::{Category, COMMANDS};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StringCommand {
     pub index: Option<usize>,
     pub name: String,
     pub case: Option<String>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GenderDefinition {
-    pub gender: String,
+    pub gender: Option<String>, //< name of the gender, None if taken from a parameter
+    pub indexref: Option<usize>, //< gender is taken from this parameter, instead of 'gender'
+    pub whitespace: Option<(String, String)>, //< whitespace around '=', if preserved
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChoiceList {
     pub name: String,
     pub indexref: Option<usize>,
     pub indexsubref: Option<usize>,
     pub choices: Vec<String>,
+    #[serde(skip)]
+    pub spans: Vec<(usize, usize)>, //< spans of the items in the source, relative to the start of the list; empty if unknown
 }
 
-#[derive(Debug, PartialEq)]
+/// Lists compare equal by content, independent of the source spans of their items.
+impl PartialEq for ChoiceList {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.indexref == other.indexref
+            && self.indexsubref == other.indexsubref
+            && self.choices == other.choices
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum FragmentContent {
     Text(String),
     Command(StringCommand),
     Gender(GenderDefinition),
     Choice(ChoiceList),
+    Verbatim(String),
+    Comment(String),
+    StringRef { id: String },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StringFragment {
     pub pos_begin: usize,
     pub pos_end: usize,
     pub content: FragmentContent,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ParsedString {
     pub fragments: Vec<StringFragment>,
 }
 
 #[derive(Debug, PartialEq)]
-pub struct ParserError {
+pub struct ParameterSlot {
+    pub index: usize,
+    pub name: String,
     pub pos_begin: usize,
+    pub pos_end: usize,
+}
+
+/// Difference between the plural lists of two strings, see ParsedString::plural_diff.
+/// Lists are numbered in order of appearance.
+#[derive(Debug, PartialEq)]
+pub enum PluralFinding {
+    Missing {
+        list: usize,
+    },
+    Extra {
+        list: usize,
+    },
+    ItemCount {
+        list: usize,
+        base: usize,
+        found: usize,
+    },
+    Reference {
+        list: usize,
+        base: Option<usize>,
+        found: Option<usize>,
+    },
+    Reordered,
+}
+
+/// Selected item for each choice list, see ParsedString::select_branches.
+/// Lists are numbered in order of appearance, starting at 0.
+pub type BranchSelections = HashMap<usize, usize>;
+
+/// Text with the name of the colour it is drawn in, see ParsedString::colored_runs.
+pub type ColouredRun = (String, Option<String>);
+
+#[derive(Debug, PartialEq)]
+pub struct ParserError {
+    pub pos_begin: usize, //< codepoint offset in the parsed string
     pub pos_end: Option<usize>,
+    pub byte_begin: usize, //< byte offset in the parsed string
+    pub byte_end: Option<usize>,
     pub message: String,
 }
 
+/// Error of ParsedString::parse_lines, with the line it belongs to.
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    pub line: usize,        //< line number, starting at 1
+    pub error: ParserError, //< positions are offsets in the line
+}
+
+/// Callbacks for walking over a parsed string, see ParsedString::accept.
+/// All methods default to doing nothing.
+pub trait Visitor {
+    fn visit_text(&mut self, _text: &str) {}
+    fn visit_command(&mut self, _command: &StringCommand) {}
+    fn visit_gender(&mut self, _gender: &GenderDefinition) {}
+    fn visit_choice(&mut self, _choice: &ChoiceList) {}
+    fn visit_verbatim(&mut self, _text: &str) {}
+    fn visit_comment(&mut self, _text: &str) {}
+    fn visit_string_ref(&mut self, _id: &str) {}
+}
+
+#[derive(Debug, Default)]
+pub struct ParseOptions {
+    pat_command: Option<Regex>,
+    preserve_gender_whitespace: bool,
+}
+
+fn command_pattern(name: &str) -> String {
+    format!(r"^\{{(?:(\d+):)?(|\{{|(?:{}))(?:\.(\w+))?\}}$", name)
+}
+
 static PAT_COMMAND: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^\{(?:(\d+):)?(|\{|[A-Z]+[A-Z0-9_]*)(?:\.(\w+))?\}$").unwrap());
+    Lazy::new(|| Regex::new(&command_pattern("[A-Z]+[A-Z0-9_]*")).unwrap());
+
+impl ParseOptions {
+    /// Options for a string format with different command names.
+    ///
+    /// @param name Regular expression matching valid command names, instead of '[A-Z]+[A-Z0-9_]*'.
+    pub fn with_command_name(name: &str) -> Result<ParseOptions, regex::Error> {
+        Ok(ParseOptions {
+            pat_command: Some(Regex::new(&command_pattern(name))?),
+            ..Default::default()
+        })
+    }
+
+    /// Keep the whitespace around '=' in gender definitions, so '{G = n}' compiles unchanged.
+    pub fn with_gender_whitespace(self) -> ParseOptions {
+        ParseOptions {
+            preserve_gender_whitespace: true,
+            ..self
+        }
+    }
+
+    fn pat_command(&self) -> &Regex {
+        self.pat_command.as_ref().unwrap_or(&PAT_COMMAND)
+    }
+}
 
 impl StringCommand {
-    fn parse(string: &str) -> Option<StringCommand> {
-        let caps = PAT_COMMAND.captures(string)?;
+    /// Estimated number of characters the command renders to.
+    /// Parameters have unknown length, their compiled form is used as an estimate.
+    fn estimated_len(&self) -> usize {
+        match COMMANDS.iter().find(|ci| ci.name == self.name) {
+            Some(info) if info.category == Category::CHARACTER => 1,
+            Some(info) if matches!(info.category, Category::COLOUR | Category::FONT) => 0,
+            _ => self.compile().chars().count(),
+        }
+    }
+
+    fn parse(string: &str, options: &ParseOptions) -> Option<StringCommand> {
+        let caps = options.pat_command().captures(string)?;
         Some(StringCommand {
             index: caps.get(1).and_then(|v| v.as_str().parse().ok()),
             name: String::from(&caps[2]),
@@ -61,7 +185,7 @@ impl StringCommand {
         })
     }
 
-    fn compile(&self) -> String {
+    pub fn compile(&self) -> String {
         let mut result = String::from("{");
         if let Some(i) = self.index {
             result.push_str(&format!("{}:", i));
@@ -70,49 +194,239 @@ impl StringCommand {
         if let Some(case) = &self.case {
             result.push_str(&format!(".{}", case));
         }
-        result.push_str("}");
+        result.push('}');
         result
     }
 }
 
-static PAT_GENDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{G\s*=\s*(\w+)\}$").unwrap());
+/// Commands are ordered by name, then by index, then by case.
+/// Commands without index or case are ordered before commands with one.
+impl Ord for StringCommand {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.name, self.index, &self.case).cmp(&(&other.name, other.index, &other.case))
+    }
+}
+
+impl PartialOrd for StringCommand {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+static PAT_GENDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\{G(\s*)=(\s*)(?:(\d+)|(\w+))\}$").unwrap());
 
 impl GenderDefinition {
-    fn parse(string: &str) -> Option<GenderDefinition> {
+    /// Parse a gender definition.
+    /// The literal form '{G=n}' names the gender, the referenced form '{G=1}' takes it from parameter 1.
+    fn parse(string: &str, options: &ParseOptions) -> Option<GenderDefinition> {
         let caps = PAT_GENDER.captures(string)?;
         Some(GenderDefinition {
-            gender: String::from(&caps[1]),
+            gender: caps.get(4).map(|v| String::from(v.as_str())),
+            indexref: caps.get(3).and_then(|v| v.as_str().parse().ok()),
+            whitespace: options
+                .preserve_gender_whitespace
+                .then(|| (String::from(&caps[1]), String::from(&caps[2]))),
         })
     }
 
+    /// The gender name, or the referenced position.
+    fn value(&self) -> String {
+        match self.indexref {
+            Some(i) => i.to_string(),
+            None => self.gender.clone().unwrap_or_default(),
+        }
+    }
+
     fn compile(&self) -> String {
-        format!("{{G={}}}", self.gender)
+        let (before, after) = self
+            .whitespace
+            .as_ref()
+            .map_or(("", ""), |(b, a)| (b.as_str(), a.as_str()));
+        format!("{{G{}={}{}}}", before, after, self.value())
     }
 }
 
 static PAT_CHOICE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^\{([PG])(?:\s+(\d+)(?::(\d+))?)?(\s+[^\s0-9].*?)\s*\}$").unwrap());
+static PAT_CHOICE_START: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{[PG]\s").unwrap());
 static PAT_ITEM: Lazy<Regex> =
     Lazy::new(|| Regex::new(r##"^\s+(?:([^\s"]+)|"([^"]*)")"##).unwrap());
 
 impl ChoiceList {
-    fn parse(string: &str) -> Option<ChoiceList> {
-        let caps = PAT_CHOICE.captures(string)?;
+    fn parse(string: &str, options: &ParseOptions) -> Result<Option<ChoiceList>, ParserError> {
+        let caps = match PAT_CHOICE.captures(string) {
+            Some(caps) => caps,
+            None => return Ok(None),
+        };
         let mut result = ChoiceList {
             name: String::from(&caps[1]),
             indexref: caps.get(2).and_then(|v| v.as_str().parse().ok()),
             indexsubref: caps.get(3).and_then(|v| v.as_str().parse().ok()),
             choices: Vec::new(),
+            spans: Vec::new(),
         };
+        let mut offset = caps.get(4).unwrap().start();
         let mut rest = &caps[4];
         while !rest.is_empty() {
-            let m = PAT_ITEM.captures(rest)?;
+            let m = match PAT_ITEM.captures(rest) {
+                Some(m) => m,
+                None if string[..offset].ends_with('"')
+                    && !rest.starts_with(char::is_whitespace) =>
+                {
+                    let pos_begin = string[..offset].chars().count();
+                    return Err(ParserError {
+                        pos_begin,
+                        pos_end: Some(pos_begin + 1),
+                        byte_begin: offset,
+                        byte_end: Some(offset + 1),
+                        message: String::from(
+                            "Choice items must be separated by whitespace, found text after closing quote.",
+                        ),
+                    });
+                }
+                None if rest.starts_with('"') => {
+                    // an unquoted item directly followed by a quote, like '{P a" "b}'
+                    let pos_begin = string[..offset].chars().count();
+                    return Err(ParserError {
+                        pos_begin,
+                        pos_end: Some(pos_begin + 1),
+                        byte_begin: offset,
+                        byte_end: Some(offset + 1),
+                        message: String::from(
+                            "Mismatched quotes in choice items, quotes must enclose a whole item.",
+                        ),
+                    });
+                }
+                None => return Ok(None),
+            };
+            let item = m.get(1).or(m.get(2)).unwrap();
+            if m.get(1).is_some()
+                && item.as_str().contains(['{', '}'])
+                && StringCommand::parse(item.as_str(), options).is_none()
+            {
+                // braces in unquoted items confuse the string command scanner, unless they form a command
+                let pos_begin = string[..offset + item.start()].chars().count();
+                return Err(ParserError {
+                    pos_begin,
+                    pos_end: Some(pos_begin + item.as_str().chars().count()),
+                    byte_begin: offset + item.start(),
+                    byte_end: Some(offset + item.end()),
+                    message: format!(
+                        "Invalid choice item '{}', braces are only allowed as part of a string command.",
+                        item.as_str()
+                    ),
+                });
+            }
+            let pos_begin = string[..offset + item.start()].chars().count();
+            result.choices.push(String::from(item.as_str()));
             result
-                .choices
-                .push(String::from(m.get(1).or(m.get(2)).unwrap().as_str()));
-            rest = &rest[m.get(0).unwrap().end()..];
+                .spans
+                .push((pos_begin, pos_begin + item.as_str().chars().count()));
+            let end = m.get(0).unwrap().end();
+            if m.get(2).is_some()
+                && item.as_str().ends_with('\\')
+                && rest[end..].starts_with(|c: char| !c.is_whitespace())
+            {
+                // quotes end at the next quote, there is no escaping
+                let pos_begin = string[..offset + item.end()].chars().count() - 1;
+                return Err(ParserError {
+                    pos_begin,
+                    pos_end: Some(pos_begin + 2),
+                    byte_begin: offset + item.end() - 1,
+                    byte_end: Some(offset + item.end() + 1),
+                    message: String::from("Quotes cannot be escaped in choice items."),
+                });
+            }
+            offset += end;
+            rest = &rest[end..];
         }
-        return Some(result);
+        Ok(Some(result))
+    }
+
+    /// Length of the longest item in characters.
+    /// Items consisting of a string command count with the estimated length of the command.
+    pub fn max_item_len(&self) -> usize {
+        self.choices
+            .iter()
+            .map(
+                |item| match StringCommand::parse(item, &ParseOptions::default()) {
+                    Some(cmd) => cmd.estimated_len(),
+                    None => item.chars().count(),
+                },
+            )
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Spans of the items in characters, relative to the start of the compiled choice list.
+    /// Quotes around an item are not part of its span.
+    pub fn item_spans(&self) -> Vec<(usize, usize)> {
+        let mut pos = self.name.chars().count() + 1;
+        if let Some(i) = self.indexref {
+            pos += i.to_string().len() + 1;
+            if let Some(s) = self.indexsubref {
+                pos += s.to_string().len() + 1;
+            }
+        }
+        let mut result = Vec::new();
+        for c in &self.choices {
+            let quoted = needs_quotes(c);
+            pos += if quoted { 2 } else { 1 };
+            let end = pos + c.chars().count();
+            result.push((pos, end));
+            pos = if quoted { end + 1 } else { end };
+        }
+        result
+    }
+
+    /// Remove quotes stored as part of the items, like '"car"' in a hand-built list.
+    /// Parsed items never contain their quotes; compile adds quotes only where needed.
+    pub fn normalize_quoting(&mut self) {
+        for c in &mut self.choices {
+            if c.len() >= 2 && c.starts_with('"') && c.ends_with('"') {
+                *c = String::from(&c[1..c.len() - 1]);
+            }
+        }
+        self.spans.clear();
+    }
+
+    /// Replace the choice items.
+    /// Items must be representable in a choice list; the list is left unchanged otherwise.
+    /// Positions of the containing string must be recomputed via ParsedString::update_positions.
+    pub fn set_choices(&mut self, items: Vec<String>) -> Result<(), ParserError> {
+        let error = |message: String| ParserError {
+            pos_begin: 0,
+            pos_end: None,
+            byte_begin: 0,
+            byte_end: None,
+            message,
+        };
+        if items.is_empty() {
+            return Err(error(format!(
+                "Choice list '{{{}}}' needs at least one item.",
+                self.name
+            )));
+        }
+        for item in &items {
+            if item.contains('"') {
+                return Err(error(format!(
+                    "Invalid choice item '{}', quotes are not allowed.",
+                    item
+                )));
+            }
+            if item.contains(['{', '}'])
+                && StringCommand::parse(item, &ParseOptions::default()).is_none()
+            {
+                return Err(error(format!(
+                    "Invalid choice item '{}', braces are only allowed as part of a string command.",
+                    item
+                )));
+            }
+        }
+        self.choices = items;
+        self.spans.clear();
+        Ok(())
     }
 
     fn compile(&self) -> String {
@@ -124,42 +438,206 @@ impl ChoiceList {
             }
         }
         for c in &self.choices {
-            if c.is_empty() || c.contains(|v| char::is_ascii_whitespace(&v)) {
+            if needs_quotes(c) {
                 result.push_str(&format!(r##" "{}""##, c));
             } else {
                 result.push_str(&format!(" {}", c));
             }
         }
-        result.push_str("}");
+        result.push('}');
         result
     }
 }
 
+/// Whether a choice item must be quoted: when it is empty, contains whitespace, or contains braces
+/// which do not form a string command.
+fn needs_quotes(item: &str) -> bool {
+    item.is_empty()
+        || item.contains(|v| char::is_ascii_whitespace(&v))
+        || (item.contains(['{', '}'])
+            && StringCommand::parse(item, &ParseOptions::default()).is_none())
+}
+
+static PAT_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)^\{#(.*)\}$").unwrap());
+static PAT_STRING_REF: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\{STRING:([A-Z_][A-Z0-9_]*)\}$").unwrap());
+static PAT_UNKNOWN_CHOICE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\{([A-Z])(?:\s+\d+(?::\d+)?)?\s+[^\s0-9]").unwrap());
+
 impl FragmentContent {
-    fn parse(string: &str) -> Result<FragmentContent, String> {
-        if let Some(command) = StringCommand::parse(string) {
+    /// Parse a string command.
+    /// Positions in errors are relative to the start of the command.
+    fn parse(string: &str, options: &ParseOptions) -> Result<FragmentContent, ParserError> {
+        if let Some(command) = StringCommand::parse(string, options) {
             Ok(FragmentContent::Command(command))
-        } else if let Some(gender) = GenderDefinition::parse(string) {
+        } else if let Some(gender) = GenderDefinition::parse(string, options) {
             Ok(FragmentContent::Gender(gender))
-        } else if let Some(choice) = ChoiceList::parse(string) {
+        } else if let Some(choice) = ChoiceList::parse(string, options)? {
             Ok(FragmentContent::Choice(choice))
+        } else if let Some(caps) = PAT_COMMENT.captures(string) {
+            Ok(FragmentContent::Comment(String::from(&caps[1])))
+        } else if let Some(caps) = PAT_STRING_REF.captures(string) {
+            Ok(FragmentContent::StringRef {
+                id: String::from(&caps[1]),
+            })
+        } else if let Some(caps) = PAT_UNKNOWN_CHOICE.captures(string) {
+            Err(ParserError {
+                pos_begin: 1,
+                pos_end: Some(2),
+                byte_begin: 1,
+                byte_end: Some(2),
+                message: format!(
+                    "Unknown choice list '{}', valid prefixes are 'P' and 'G'.",
+                    &caps[1]
+                ),
+            })
         } else {
-            Err(format!("Invalid string command: '{}'", string))
+            Err(ParserError {
+                pos_begin: 0,
+                pos_end: Some(string.chars().count()),
+                byte_begin: 0,
+                byte_end: Some(string.len()),
+                message: format!("Invalid string command: '{}'", string),
+            })
         }
     }
 
-    fn compile(&self) -> String {
+    pub fn compile(&self) -> String {
         match self {
             Self::Text(s) => s.clone(),
             Self::Command(command) => command.compile(),
             Self::Gender(gender) => gender.compile(),
             Self::Choice(choice) => choice.compile(),
+            Self::Verbatim(text) => format!("{{RAW {}}}", text),
+            Self::Comment(text) => format!("{{#{}}}", text),
+            Self::StringRef { id } => format!("{{STRING:{}}}", id),
+        }
+    }
+}
+
+/// Maximum number of variants ParsedString::render_all_branches produces.
+const MAX_BRANCHES: usize = 256;
+
+impl StringFragment {
+    /// Get the command as written in the source, before any normalization by compile.
+    /// `source` is the string which was parsed.
+    ///
+    /// @returns The source of the fragment, or None for text or if the fragment is not part of `source`.
+    pub fn raw_command<'a>(&self, source: &'a str) -> Option<&'a str> {
+        if matches!(self.content, FragmentContent::Text(_)) {
+            return None;
+        }
+        let mut indices = source
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(source.len()));
+        let begin = indices.nth(self.pos_begin)?;
+        let end = indices.nth(self.pos_end - self.pos_begin - 1)?;
+        Some(&source[begin..end])
+    }
+
+    /// Spans of the items of a choice list, in characters of the parsed string.
+    /// The spans are recorded while parsing; None if they are not known, like for a hand-built list.
+    pub fn choice_item_spans(&self) -> Option<Vec<(usize, usize)>> {
+        let FragmentContent::Choice(choice) = &self.content else {
+            return None;
+        };
+        if choice.spans.len() != choice.choices.len() {
+            return None;
+        }
+        let spans = choice.spans.iter();
+        Some(
+            spans
+                .map(|(begin, end)| (self.pos_begin + begin, self.pos_begin + end))
+                .collect(),
+        )
+    }
+}
+
+/// Find the end of a verbatim region '{RAW ...}' or a comment '{#...}'.
+/// Braces inside the region must be balanced.
+///
+/// @returns Byte position of the closing brace.
+fn find_verbatim_end(string: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in string.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Find the closing brace matching an opening brace, for example for bracket matching in editors.
+/// Nested braces, like in verbatim regions or choice items, must be balanced.
+/// The literal brace '{{}' is a command by itself, and does not open a nested brace.
+///
+/// @returns Character position of the closing brace, or None if `open_pos` is not an opening brace or it is not closed.
+pub fn matching_brace(source: &str, open_pos: usize) -> Option<usize> {
+    let chars: Vec<char> = source.chars().collect();
+    if chars.get(open_pos) != Some(&'{') {
+        return None;
+    }
+    let mut depth = 0;
+    let mut i = open_pos;
+    while i < chars.len() {
+        match chars[i] {
+            '{' if chars[i + 1..].starts_with(&['{', '}']) => {
+                if depth == 0 {
+                    return Some(i + 2);
+                }
+                i += 3;
+                continue;
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Find the end of a string command, which starts at the beginning of `string`.
+/// Braces inside quoted items of choice lists do not end the command. If the quotes do not
+/// pair up, the first closing brace ends the command, so the choice list reports the error.
+///
+/// @returns Byte position of the closing brace.
+fn find_command_end(string: &str) -> Option<usize> {
+    if PAT_CHOICE_START.is_match(string) {
+        let mut quoted = false;
+        for (i, c) in string.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '}' if !quoted => return Some(i),
+                _ => (),
+            }
         }
     }
+    string.find('}')
 }
 
 impl ParsedString {
     pub fn parse(string: &str) -> Result<ParsedString, ParserError> {
+        ParsedString::parse_with_options(string, &ParseOptions::default())
+    }
+
+    pub fn parse_with_options(
+        string: &str,
+        options: &ParseOptions,
+    ) -> Result<ParsedString, ParserError> {
         let mut result = ParsedString {
             fragments: Vec::new(),
         };
@@ -178,21 +656,65 @@ impl ParsedString {
                     });
                     pos_code += len_code;
                 }
-                if let Some(end) = rest.find('}') {
+                let pos_byte = string.len() - rest.len();
+                if rest.starts_with("{RAW ") {
+                    if let Some(end) = find_verbatim_end(rest) {
+                        let text: &str;
+                        (text, rest) = rest.split_at(end + 1);
+                        let len_code = text.chars().count();
+                        result.fragments.push(StringFragment {
+                            pos_begin: pos_code,
+                            pos_end: pos_code + len_code,
+                            content: FragmentContent::Verbatim(String::from(&text[5..end])),
+                        });
+                        pos_code += len_code;
+                    } else {
+                        return Err(ParserError {
+                            pos_begin: pos_code,
+                            pos_end: None,
+                            byte_begin: pos_byte,
+                            byte_end: None,
+                            message: String::from("Unterminated verbatim region, '}' expected."),
+                        });
+                    }
+                } else if rest.starts_with("{#") {
+                    // comments may mention commands, like '{# keep {RED} here}'
+                    if let Some(end) = find_verbatim_end(rest) {
+                        let text: &str;
+                        (text, rest) = rest.split_at(end + 1);
+                        let len_code = text.chars().count();
+                        result.fragments.push(StringFragment {
+                            pos_begin: pos_code,
+                            pos_end: pos_code + len_code,
+                            content: FragmentContent::Comment(String::from(&text[2..end])),
+                        });
+                        pos_code += len_code;
+                    } else {
+                        return Err(ParserError {
+                            pos_begin: pos_code,
+                            pos_end: None,
+                            byte_begin: pos_byte,
+                            byte_end: None,
+                            message: String::from("Unterminated comment, '}' expected."),
+                        });
+                    }
+                } else if let Some(end) = find_command_end(rest) {
                     let text: &str;
                     (text, rest) = rest.split_at(end + 1);
                     let len_code = text.chars().count();
-                    match FragmentContent::parse(text) {
+                    match FragmentContent::parse(text, options) {
                         Ok(content) => result.fragments.push(StringFragment {
                             pos_begin: pos_code,
                             pos_end: pos_code + len_code,
-                            content: content,
+                            content,
                         }),
-                        Err(message) => {
+                        Err(err) => {
                             return Err(ParserError {
-                                pos_begin: pos_code,
-                                pos_end: Some(pos_code + len_code),
-                                message: message,
+                                pos_begin: pos_code + err.pos_begin,
+                                pos_end: err.pos_end.map(|p| pos_code + p),
+                                byte_begin: pos_byte + err.byte_begin,
+                                byte_end: err.byte_end.map(|p| pos_byte + p),
+                                message: err.message,
                             });
                         }
                     };
@@ -201,6 +723,8 @@ impl ParsedString {
                     return Err(ParserError {
                         pos_begin: pos_code,
                         pos_end: None,
+                        byte_begin: pos_byte,
+                        byte_end: None,
                         message: String::from("Unterminated string command, '}' expected."),
                     });
                 }
@@ -217,6 +741,95 @@ impl ParsedString {
         Ok(result)
     }
 
+    /// Parse each line of the input as a separate string.
+    /// Errors contain the line number; their positions are relative to the line.
+    pub fn parse_lines(input: &str) -> Vec<Result<ParsedString, LineError>> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(lineno, line)| {
+                ParsedString::parse(line).map_err(|error| LineError {
+                    line: lineno + 1,
+                    error,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse a string, which may be preceded by comment lines starting with '//' or '#'.
+    /// The comment is returned as is, without the comment markers; it is not validated.
+    /// Error positions are relative to the string, after the comment lines.
+    pub fn parse_with_leading_comment(
+        input: &str,
+    ) -> Result<(Option<String>, ParsedString), ParserError> {
+        let mut comment: Vec<&str> = Vec::new();
+        let mut rest = input;
+        while let Some((line, next)) = rest.split_once('\n') {
+            let line = line.trim_end_matches('\r');
+            match line.strip_prefix("//").or(line.strip_prefix('#')) {
+                Some(text) => comment.push(text.strip_prefix(' ').unwrap_or(text)),
+                None => break,
+            }
+            rest = next;
+        }
+        let parsed = ParsedString::parse(rest)?;
+        Ok(((!comment.is_empty()).then(|| comment.join("\n")), parsed))
+    }
+
+    /// Parse a string from raw bytes, which must be valid UTF-8.
+    /// For invalid UTF-8 the error covers the invalid sequence; for a truncated sequence at
+    /// the end of the input, it has no end.
+    pub fn parse_bytes(bytes: &[u8]) -> Result<ParsedString, ParserError> {
+        match std::str::from_utf8(bytes) {
+            Ok(string) => ParsedString::parse(string),
+            Err(err) => {
+                let valid = err.valid_up_to();
+                let pos_code = std::str::from_utf8(&bytes[..valid])
+                    .unwrap()
+                    .chars()
+                    .count();
+                Err(ParserError {
+                    pos_begin: pos_code,
+                    pos_end: err.error_len().map(|_| pos_code + 1),
+                    byte_begin: valid,
+                    byte_end: err.error_len().map(|len| valid + len),
+                    message: format!("Invalid UTF-8 sequence at byte {}.", valid),
+                })
+            }
+        }
+    }
+
+    /// Load a parsed string from its JSON serialization.
+    /// The fragments are checked against their compiled form, and positions are recomputed.
+    pub fn from_json(json: &str) -> Result<ParsedString, ParserError> {
+        ParsedString::from_json_with_options(json, &ParseOptions::default())
+    }
+
+    /// Load a parsed string from its JSON serialization, which was parsed with `options`.
+    pub fn from_json_with_options(
+        json: &str,
+        options: &ParseOptions,
+    ) -> Result<ParsedString, ParserError> {
+        let loaded: ParsedString = serde_json::from_str(json).map_err(|err| ParserError {
+            pos_begin: 0,
+            pos_end: None,
+            byte_begin: 0,
+            byte_end: None,
+            message: format!("Invalid JSON: {}", err),
+        })?;
+        let result = ParsedString::parse_with_options(&loaded.compile(), options)?;
+        if !result.equals_ignoring_positions(&loaded) {
+            return Err(ParserError {
+                pos_begin: 0,
+                pos_end: None,
+                byte_begin: 0,
+                byte_end: None,
+                message: String::from("Fragments do not match their compiled form."),
+            });
+        }
+        Ok(result)
+    }
+
     pub fn compile(&self) -> String {
         let mut result = String::new();
         for f in &self.fragments {
@@ -224,275 +837,1381 @@ impl ParsedString {
         }
         result
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Compile the string without its text, as a skeleton for a translation.
+    /// All other fragments are kept as is, including the items of choice lists.
+    pub fn to_template(&self) -> String {
+        self.fragments
+            .iter()
+            .filter(|f| !matches!(f.content, FragmentContent::Text(_)))
+            .map(|f| f.content.compile())
+            .collect()
+    }
 
-    #[test]
-    fn test_parse_cmd_ok() {
-        assert_eq!(
-            FragmentContent::parse("{}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: None,
-                name: String::from(""),
-                case: None
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{{}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: None,
-                name: String::from("{"),
-                case: None
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{BIG_FONT}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: None,
-                name: String::from("BIG_FONT"),
-                case: None
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{NUM}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: None,
-                name: String::from("NUM"),
-                case: None
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{1:RED}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: Some(1),
-                name: String::from("RED"),
-                case: None
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{STRING.gen}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: None,
-                name: String::from("STRING"),
-                case: Some(String::from("gen"))
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{1:STRING.gen}"),
-            Ok(FragmentContent::Command(StringCommand {
-                index: Some(1),
-                name: String::from("STRING"),
-                case: Some(String::from("gen"))
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{G=n}"),
-            Ok(FragmentContent::Gender(GenderDefinition {
-                gender: String::from("n")
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{G = n}"),
-            Ok(FragmentContent::Gender(GenderDefinition {
-                gender: String::from("n")
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{P a b}"),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: None,
-                indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{P\na\tb}"),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: None,
-                indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse(r##"{P "" b}"##),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: None,
-                indexsubref: None,
-                choices: vec![String::from(""), String::from("b")]
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse(r##"{P "a b" "c"}"##),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: None,
-                indexsubref: None,
-                choices: vec![String::from("a b"), String::from("c")]
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{P 1 a b}"),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
-            }))
-        );
-        assert_eq!(
-            FragmentContent::parse("{P\t1\na\rb\n}"),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
-            }))
+    /// Length of the compiled string in characters, without building it.
+    pub fn compiled_len(&self) -> usize {
+        self.fragments
+            .iter()
+            .map(|f| match &f.content {
+                FragmentContent::Text(text) => text.chars().count(),
+                content => content.compile().chars().count(),
+            })
+            .sum()
+    }
+
+    /// Whether the string consists of only text, without any commands, genders or choices.
+    /// An empty string is considered plain text.
+    pub fn is_plain_text(&self) -> bool {
+        self.fragments
+            .iter()
+            .all(|f| matches!(f.content, FragmentContent::Text(_)))
+    }
+
+    /// List the text of the string, with the colour it is drawn in.
+    /// The colour is the name of the last colour command, like 'RED', or None before any colour
+    /// command. '{PUSH_COLOUR}' and '{POP_COLOUR}' save and restore the colour.
+    /// Choice lists are skipped, as their text depends on the parameters.
+    pub fn colored_runs(&self) -> Vec<ColouredRun> {
+        self.walk_colours().0
+    }
+
+    /// Walk over the string, tracking the colour like colored_runs.
+    ///
+    /// @returns The text runs, the colour at the end, and the number of colours pushed but not popped.
+    fn walk_colours(&self) -> (Vec<ColouredRun>, Option<String>, usize) {
+        let mut result = Vec::new();
+        let mut colour: Option<String> = None;
+        let mut stack = Vec::new();
+        for f in &self.fragments {
+            match &f.content {
+                FragmentContent::Text(text) => result.push((text.clone(), colour.clone())),
+                FragmentContent::Command(cmd) => match cmd.name.as_str() {
+                    "PUSH_COLOUR" => stack.push(colour.clone()),
+                    "POP_COLOUR" => colour = stack.pop().flatten(),
+                    name if COMMANDS
+                        .iter()
+                        .any(|ci| ci.name == name && ci.category == Category::COLOUR) =>
+                    {
+                        colour = Some(String::from(name))
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+        (result, colour, stack.len())
+    }
+
+    /// Whether another string can be appended to this string, without this string affecting it.
+    /// The colour must be back at the default, either no colour or '{BLACK}', with all pushed
+    /// colours popped; and the string must not end with a gender list, which would refer to a
+    /// parameter of the appended string.
+    pub fn is_concat_safe(&self) -> bool {
+        let (_, colour, pushed) = self.walk_colours();
+        let dangling = self
+            .fragments
+            .iter()
+            .rev()
+            .find(|f| !matches!(f.content, FragmentContent::Comment(_)))
+            .is_some_and(|f| match &f.content {
+                FragmentContent::Choice(choice) => choice.name == "G" && choice.indexref.is_none(),
+                _ => false,
+            });
+        pushed == 0 && colour.as_deref().is_none_or(|c| c == "BLACK") && !dangling
+    }
+
+    /// Gender of the first gender definition, like 'f' for '{G=f}'.
+    /// Definitions taking the gender from a parameter have no declared gender.
+    pub fn declared_gender(&self) -> Option<&str> {
+        self.fragments.iter().find_map(|f| match &f.content {
+            FragmentContent::Gender(g) => g.gender.as_deref(),
+            _ => None,
+        })
+    }
+
+    /// Error covering a fragment; byte positions are taken from the compiled string.
+    fn fragment_error(&self, fragment: &StringFragment, message: String) -> ParserError {
+        let compiled = self.compile();
+        let byte_pos = |pos: usize| {
+            compiled
+                .char_indices()
+                .nth(pos)
+                .map_or(compiled.len(), |(i, _)| i)
+        };
+        ParserError {
+            pos_begin: fragment.pos_begin,
+            pos_end: Some(fragment.pos_end),
+            byte_begin: byte_pos(fragment.pos_begin),
+            byte_end: Some(byte_pos(fragment.pos_end)),
+            message,
+        }
+    }
+
+    /// Compile the string, checking that all string commands have valid names.
+    /// Commands built by hand, like '{num}', would otherwise be emitted as is.
+    pub fn try_compile(&self) -> Result<String, ParserError> {
+        for f in &self.fragments {
+            if let FragmentContent::Command(cmd) = &f.content {
+                if StringCommand::parse(&cmd.compile(), &ParseOptions::default()).as_ref()
+                    != Some(cmd)
+                {
+                    return Err(self.fragment_error(
+                        f,
+                        format!("Invalid string command name '{}'.", cmd.name),
+                    ));
+                }
+            }
+        }
+        Ok(self.compile())
+    }
+
+    /// Dump the parsed string for debugging, one fragment per line.
+    /// Choice items are listed indented below their choice list.
+    pub fn tree_string(&self) -> String {
+        let mut result = String::new();
+        for f in &self.fragments {
+            let pos = format!("{}..{}", f.pos_begin, f.pos_end);
+            match &f.content {
+                FragmentContent::Text(text) => {
+                    result.push_str(&format!("{} Text {:?}\n", pos, text));
+                }
+                FragmentContent::Command(cmd) => {
+                    result.push_str(&format!("{} Command {}\n", pos, cmd.compile()));
+                }
+                FragmentContent::Gender(gender) => {
+                    result.push_str(&format!("{} Gender {}\n", pos, gender.value()));
+                }
+                FragmentContent::Choice(choice) => {
+                    let mut head = choice.name.clone();
+                    if let Some(i) = choice.indexref {
+                        head.push_str(&format!(" {}", i));
+                        if let Some(s) = choice.indexsubref {
+                            head.push_str(&format!(":{}", s));
+                        }
+                    }
+                    result.push_str(&format!("{} Choice {}\n", pos, head));
+                    for (i, item) in choice.choices.iter().enumerate() {
+                        result.push_str(&format!("  {}: {:?}\n", i, item));
+                    }
+                }
+                FragmentContent::Verbatim(text) => {
+                    result.push_str(&format!("{} Verbatim {:?}\n", pos, text));
+                }
+                FragmentContent::Comment(text) => {
+                    result.push_str(&format!("{} Comment {:?}\n", pos, text));
+                }
+                FragmentContent::StringRef { id } => {
+                    result.push_str(&format!("{} StringRef {}\n", pos, id));
+                }
+            }
+        }
+        result
+    }
+
+    /// Compact summary of the string commands, for logging and quick comparison.
+    /// Commands are listed sorted, followed by the choice lists with their number of items.
+    /// For example: "NUM,STRING.gen,P[2]".
+    pub fn signature_string(&self) -> String {
+        let mut commands = Vec::new();
+        let mut choices = Vec::new();
+        for f in &self.fragments {
+            match &f.content {
+                FragmentContent::Command(cmd) => match &cmd.case {
+                    Some(case) => commands.push(format!("{}.{}", cmd.name, case)),
+                    None => commands.push(cmd.name.clone()),
+                },
+                FragmentContent::Choice(choice) => {
+                    choices.push(format!("{}[{}]", choice.name, choice.choices.len()))
+                }
+                _ => (),
+            }
+        }
+        commands.sort();
+        choices.sort();
+        commands.append(&mut choices);
+        commands.join(",")
+    }
+
+    /// Walk over all fragments, calling the matching method of the visitor.
+    /// Choice items are visited as well, directly after their choice list.
+    pub fn accept<V: Visitor>(&self, visitor: &mut V) {
+        for fragment in &self.fragments {
+            match &fragment.content {
+                FragmentContent::Text(text) => visitor.visit_text(text),
+                FragmentContent::Command(cmd) => visitor.visit_command(cmd),
+                FragmentContent::Gender(gender) => visitor.visit_gender(gender),
+                FragmentContent::Choice(choice) => {
+                    visitor.visit_choice(choice);
+                    for item in &choice.choices {
+                        if let Ok(parsed) = ParsedString::parse(item) {
+                            parsed.accept(visitor);
+                        }
+                    }
+                }
+                FragmentContent::Verbatim(text) => visitor.visit_verbatim(text),
+                FragmentContent::Comment(text) => visitor.visit_comment(text),
+                FragmentContent::StringRef { id } => visitor.visit_string_ref(id),
+            }
+        }
+    }
+
+    /// Render every combination of choice list items, for reviewing all plural and gender forms.
+    /// String commands are kept as is; gender definitions and comments are left out.
+    pub fn render_all_branches(&self) -> Result<Vec<String>, ParserError> {
+        let mut variants = vec![String::new()];
+        for f in &self.fragments {
+            match &f.content {
+                FragmentContent::Choice(choice) => {
+                    if variants.len() * choice.choices.len() > MAX_BRANCHES {
+                        return Err(self.fragment_error(
+                            f,
+                            format!(
+                                "Too many combinations of choices, at most {} are rendered.",
+                                MAX_BRANCHES
+                            ),
+                        ));
+                    }
+                    variants = variants
+                        .iter()
+                        .flat_map(|v| choice.choices.iter().map(move |c| format!("{}{}", v, c)))
+                        .collect();
+                }
+                FragmentContent::Gender(_) | FragmentContent::Comment(_) => (),
+                FragmentContent::Text(text) | FragmentContent::Verbatim(text) => {
+                    variants.iter_mut().for_each(|v| v.push_str(text));
+                }
+                content => {
+                    let text = content.compile();
+                    variants.iter_mut().for_each(|v| v.push_str(&text));
+                }
+            }
+        }
+        Ok(variants)
+    }
+
+    /// Replace each choice list with its selected item, and remove gender definitions.
+    /// All choice lists must have a valid selection.
+    pub fn select_branches(
+        &self,
+        selections: &BranchSelections,
+    ) -> Result<ParsedString, ParserError> {
+        let mut result = String::new();
+        let mut list = 0;
+        for f in &self.fragments {
+            match &f.content {
+                FragmentContent::Choice(choice) => {
+                    let item = selections
+                        .get(&list)
+                        .and_then(|i| choice.choices.get(*i))
+                        .ok_or_else(|| {
+                            self.fragment_error(
+                                f,
+                                format!("No valid selection for choice list {}.", list),
+                            )
+                        })?;
+                    result.push_str(item);
+                    list += 1;
+                }
+                FragmentContent::Gender(_) => (),
+                content => result.push_str(&content.compile()),
+            }
+        }
+        ParsedString::parse(&result)
+    }
+
+    /// Estimate the maximum rendered length in characters, using the longest item of each choice list.
+    /// Parameters are estimated by the length of their command.
+    pub fn worst_case_length(&self) -> usize {
+        self.fragments
+            .iter()
+            .map(|f| match &f.content {
+                FragmentContent::Text(text) | FragmentContent::Verbatim(text) => {
+                    text.chars().count()
+                }
+                FragmentContent::Command(cmd) => cmd.estimated_len(),
+                FragmentContent::Choice(choice) => choice.max_item_len(),
+                FragmentContent::StringRef { .. } => f.pos_end - f.pos_begin,
+                FragmentContent::Gender(_) | FragmentContent::Comment(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Recompute the positions of all fragments from their compiled form.
+    /// Call this after modifying fragments, for example via ChoiceList::set_choices.
+    pub fn update_positions(&mut self) {
+        let mut pos_code = 0;
+        for f in &mut self.fragments {
+            let len_code = f.content.compile().chars().count();
+            f.pos_begin = pos_code;
+            f.pos_end = pos_code + len_code;
+            pos_code += len_code;
+            if let FragmentContent::Choice(choice) = &mut f.content {
+                choice.spans = choice.item_spans();
+            }
+        }
+    }
+
+    /// Iterate over the fragments which overlap a range of character positions, including partial overlaps.
+    pub fn fragments_in_range(&self, range: Range<usize>) -> impl Iterator<Item = &StringFragment> {
+        self.fragments
+            .iter()
+            .filter(move |f| f.pos_begin < range.end && f.pos_end > range.start)
+    }
+
+    /// Split the string in two at a character position.
+    /// Text is split at the position; other fragments are never split, and go to the second
+    /// half if the position lies inside them.
+    /// Positions are recomputed for both halves.
+    pub fn split_at_pos(&self, pos: usize) -> (ParsedString, ParsedString) {
+        let mut first = ParsedString {
+            fragments: Vec::new(),
+        };
+        let mut second = ParsedString {
+            fragments: Vec::new(),
+        };
+        for f in &self.fragments {
+            if f.pos_end <= pos {
+                first.fragments.push(f.clone());
+            } else if f.pos_begin >= pos {
+                second.fragments.push(f.clone());
+            } else if let FragmentContent::Text(text) = &f.content {
+                let split = text
+                    .char_indices()
+                    .nth(pos - f.pos_begin)
+                    .map_or(text.len(), |(i, _)| i);
+                first.fragments.push(StringFragment {
+                    pos_begin: 0,
+                    pos_end: 0,
+                    content: FragmentContent::Text(String::from(&text[..split])),
+                });
+                second.fragments.push(StringFragment {
+                    pos_begin: 0,
+                    pos_end: 0,
+                    content: FragmentContent::Text(String::from(&text[split..])),
+                });
+            } else {
+                second.fragments.push(f.clone());
+            }
+        }
+        first.update_positions();
+        second.update_positions();
+        (first, second)
+    }
+
+    /// Remove all comments '{#...}'.
+    /// Positions of the remaining fragments are recomputed.
+    pub fn strip_comments(&mut self) {
+        let mut fragments: Vec<StringFragment> = Vec::new();
+        for f in self.fragments.drain(..) {
+            match (fragments.last_mut().map(|l| &mut l.content), f.content) {
+                (_, FragmentContent::Comment(_)) => (),
+                (Some(FragmentContent::Text(last)), FragmentContent::Text(text)) => {
+                    last.push_str(&text)
+                }
+                (_, content) => fragments.push(StringFragment { content, ..f }),
+            }
+        }
+        self.fragments = fragments;
+        self.update_positions();
+    }
+
+    /// Iterate over the commands consuming parameters, with their resolved position.
+    fn parameter_fragments(
+        &self,
+    ) -> impl Iterator<Item = (usize, &StringFragment, &StringCommand)> {
+        let mut pos = 0;
+        self.fragments
+            .iter()
+            .filter_map(move |fragment| match &fragment.content {
+                FragmentContent::Command(cmd)
+                    if COMMANDS
+                        .iter()
+                        .any(|ci| ci.name == cmd.name && ci.arity() > 0) =>
+                {
+                    if let Some(index) = cmd.index {
+                        pos = index;
+                    }
+                    pos += 1;
+                    Some((pos - 1, fragment, cmd))
+                }
+                _ => None,
+            })
+    }
+
+    /// Iterate over the commands consuming parameters, with their resolved position.
+    /// Unknown commands are skipped.
+    pub fn indexed_commands(&self) -> impl Iterator<Item = (usize, &StringCommand)> {
+        self.parameter_fragments().map(|(pos, _, cmd)| (pos, cmd))
+    }
+
+    /// List the commands a translation must keep: those consuming parameters, in order of appearance.
+    /// Other commands, like colours and fonts, are optional. Unknown commands are skipped.
+    pub fn required_commands(&self) -> Vec<StringCommand> {
+        self.parameter_fragments()
+            .map(|(_, _, cmd)| cmd.clone())
+            .collect()
+    }
+
+    /// Get the spans of all commands consuming parameters, in order of appearance.
+    /// Commands without parameters, like colours, and unknown commands are skipped.
+    pub fn parameter_spans(&self) -> Vec<(usize, usize)> {
+        self.parameter_fragments()
+            .map(|(_, fragment, _)| (fragment.pos_begin, fragment.pos_end))
+            .collect()
+    }
+
+    /// List the parameters consumed by the string commands, with their resolved position.
+    /// Unknown commands are skipped.
+    pub fn parameters(&self) -> Vec<ParameterSlot> {
+        self.parameter_fragments()
+            .map(|(pos, fragment, cmd)| ParameterSlot {
+                index: pos,
+                name: cmd.name.clone(),
+                pos_begin: fragment.pos_begin,
+                pos_end: fragment.pos_end,
+            })
+            .collect()
+    }
+
+    /// List the choice lists, with the resolved position of the parameter they refer to.
+    /// Plural lists refer to the previous parameter, gender lists to the next one, unless a position is given.
+    pub fn choice_refs(&self) -> Vec<(&StringFragment, &ChoiceList, Option<usize>)> {
+        let mut parameters = self.parameter_fragments().peekable();
+        let mut previous = None;
+        let mut result = Vec::new();
+        for fragment in &self.fragments {
+            if let Some((pos, _, _)) = parameters.next_if(|(_, f, _)| std::ptr::eq(*f, fragment)) {
+                previous = Some(pos);
+            } else if let FragmentContent::Choice(choice) = &fragment.content {
+                let implicit = match choice.name.as_str() {
+                    "P" => previous,
+                    _ => Some(previous.map_or(0, |p| p + 1)),
+                };
+                result.push((fragment, choice, choice.indexref.or(implicit)));
+            }
+        }
+        result
+    }
+
+    /// Compare the string commands with those of another string, as multisets.
+    ///
+    /// @returns The commands only found in self, and the commands only found in base.
+    pub fn command_diff(&self, base: &ParsedString) -> (Vec<StringCommand>, Vec<StringCommand>) {
+        self.command_diff_with_case(base, true)
+    }
+
+    /// Compare the string commands with those of another string, as multisets.
+    /// With `case_sensitive` false, command names which only differ in case, like '{string}'
+    /// and '{STRING}', are the same command. Command names in OpenTTD are case-sensitive.
+    ///
+    /// @returns The commands only found in self, and the commands only found in base.
+    pub fn command_diff_with_case(
+        &self,
+        base: &ParsedString,
+        case_sensitive: bool,
+    ) -> (Vec<StringCommand>, Vec<StringCommand>) {
+        let commands = |parsed: &ParsedString| -> Vec<StringCommand> {
+            parsed
+                .fragments
+                .iter()
+                .filter_map(|f| match &f.content {
+                    FragmentContent::Command(cmd) => Some(cmd.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut only_base = commands(base);
+        let mut only_self = Vec::new();
+        for cmd in commands(self) {
+            let same = |c: &StringCommand| {
+                c.index == cmd.index
+                    && c.case == cmd.case
+                    && if case_sensitive {
+                        c.name == cmd.name
+                    } else {
+                        c.name.eq_ignore_ascii_case(&cmd.name)
+                    }
+            };
+            match only_base.iter().position(same) {
+                Some(i) => {
+                    only_base.remove(i);
+                }
+                None => only_self.push(cmd),
+            }
+        }
+        (only_self, only_base)
+    }
+
+    /// Score between 0.0 and 1.0 for how completely this translation mirrors the base.
+    ///
+    /// Each string command of the base is one point, which is scored when the translation
+    /// contains the same command (see command_diff). Text is one more point, scored when the
+    /// translation has non-blank text, or the base has none. Each command the base does not
+    /// have adds a point which is never scored. A blank translation scores 0.0.
+    pub fn completeness(&self, base: &ParsedString) -> f32 {
+        let has_text = |parsed: &ParsedString| {
+            parsed.fragments.iter().any(|f| match &f.content {
+                FragmentContent::Text(t) => !t.trim().is_empty(),
+                _ => false,
+            })
+        };
+        let blank = self.fragments.iter().all(|f| match &f.content {
+            FragmentContent::Text(t) => t.trim().is_empty(),
+            FragmentContent::Comment(_) => true,
+            _ => false,
+        });
+        if blank {
+            return 0.0;
+        }
+
+        let (only_self, only_base) = self.command_diff(base);
+        let base_commands = base
+            .fragments
+            .iter()
+            .filter(|f| matches!(f.content, FragmentContent::Command(_)))
+            .count();
+        let text_score = if has_text(self) || !has_text(base) {
+            1
+        } else {
+            0
+        };
+        let scored = base_commands - only_base.len() + text_score;
+        let total = base_commands + 1 + only_self.len();
+        scored as f32 / total as f32
+    }
+
+    /// List the plural lists, with their number of items and the position of the referenced parameter.
+    fn plural_lists(&self) -> Vec<(usize, Option<usize>)> {
+        self.choice_refs()
+            .into_iter()
+            .filter(|(_, choice, _)| choice.name == "P")
+            .map(|(_, choice, ref_pos)| (choice.choices.len(), ref_pos))
+            .collect()
+    }
+
+    /// Compare the plural lists with those of another string.
+    /// Lists are compared in order; reordered references are reported once.
+    pub fn plural_diff(&self, base: &ParsedString) -> Vec<PluralFinding> {
+        let lists = self.plural_lists();
+        let base_lists = base.plural_lists();
+        let mut result = Vec::new();
+
+        let mut refs: Vec<Option<usize>> = lists.iter().map(|l| l.1).collect();
+        let mut base_refs: Vec<Option<usize>> = base_lists.iter().map(|l| l.1).collect();
+        let reordered = refs != base_refs && {
+            refs.sort();
+            base_refs.sort();
+            refs == base_refs
+        };
+        if reordered {
+            result.push(PluralFinding::Reordered);
+        }
+
+        for (list, (found, base)) in lists.iter().zip(base_lists.iter()).enumerate() {
+            if found.0 != base.0 {
+                result.push(PluralFinding::ItemCount {
+                    list,
+                    base: base.0,
+                    found: found.0,
+                });
+            }
+            if !reordered && found.1 != base.1 {
+                result.push(PluralFinding::Reference {
+                    list,
+                    base: base.1,
+                    found: found.1,
+                });
+            }
+        }
+        for list in base_lists.len()..lists.len() {
+            result.push(PluralFinding::Extra { list });
+        }
+        for list in lists.len()..base_lists.len() {
+            result.push(PluralFinding::Missing { list });
+        }
+        result
+    }
+
+    /// Get the whitespace at the end of the string.
+    pub fn trailing_whitespace(&self) -> &str {
+        match self.fragments.last().map(|f| &f.content) {
+            Some(FragmentContent::Text(text)) => &text[text.trim_end().len()..],
+            _ => "",
+        }
+    }
+
+    /// Change the whitespace at the end of the string to match the base string.
+    ///
+    /// @returns Whether the string was changed.
+    pub fn fix_trailing_whitespace(&mut self, base: &ParsedString) -> bool {
+        let whitespace = base.trailing_whitespace();
+        if self.trailing_whitespace() == whitespace {
+            return false;
+        }
+        let pos_code = self.fragments.last().map_or(0, |f| f.pos_end);
+        match self.fragments.last_mut() {
+            Some(StringFragment {
+                pos_begin,
+                pos_end,
+                content: FragmentContent::Text(text),
+            }) => {
+                text.truncate(text.trim_end().len());
+                text.push_str(whitespace);
+                *pos_end = *pos_begin + text.chars().count();
+                if text.is_empty() {
+                    self.fragments.pop();
+                }
+            }
+            _ => self.fragments.push(StringFragment {
+                pos_begin: pos_code,
+                pos_end: pos_code + whitespace.chars().count(),
+                content: FragmentContent::Text(String::from(whitespace)),
+            }),
+        }
+        true
+    }
+
+    /// Compare the content of two parsed strings, ignoring the positions of the fragments.
+    pub fn equals_ignoring_positions(&self, other: &ParsedString) -> bool {
+        self.fragments.len() == other.fragments.len()
+            && self
+                .fragments
+                .iter()
+                .zip(&other.fragments)
+                .all(|(a, b)| a.content == b.content)
+    }
+
+    /// Update the parsed string after an edit of its source.
+    ///
+    /// `source` is the complete source after the edit, `changed` the byte range in `source` which was modified.
+    /// Only text fragments touching the edit are parsed again, all other fragments are kept and moved.
+    /// If the edit touches a string command, the whole source is parsed again.
+    /// On error, the parsed string is left unchanged.
+    pub fn reparse_range(
+        &mut self,
+        source: &str,
+        changed: Range<usize>,
+    ) -> Result<(), ParserError> {
+        let old_len = self.fragments.last().map_or(0, |f| f.pos_end);
+        let new_len = source.chars().count();
+        let changed_begin = source[..changed.start].chars().count();
+        let changed_end = source[..changed.end].chars().count();
+        let old_changed_end = (changed_end + old_len).checked_sub(new_len);
+
+        let first = self
+            .fragments
+            .iter()
+            .position(|f| f.pos_end >= changed_begin);
+        let last =
+            old_changed_end.and_then(|end| self.fragments.iter().rposition(|f| f.pos_begin < end));
+        let (first, last) = match (first, last) {
+            (Some(first), Some(last)) if first <= last => (first, last),
+            _ => {
+                *self = ParsedString::parse(source)?;
+                return Ok(());
+            }
+        };
+        if self.fragments[first..=last]
+            .iter()
+            .any(|f| !matches!(f.content, FragmentContent::Text(_)))
+        {
+            *self = ParsedString::parse(source)?;
+            return Ok(());
+        }
+
+        let region_begin = self.fragments[first].pos_begin;
+        let region_end = self.fragments[last].pos_end + new_len - old_len;
+        let byte_pos = |pos: usize| {
+            source
+                .char_indices()
+                .nth(pos)
+                .map_or(source.len(), |(b, _)| b)
+        };
+        let mut region =
+            match ParsedString::parse(&source[byte_pos(region_begin)..byte_pos(region_end)]) {
+                Ok(region) => region,
+                Err(_) => {
+                    *self = ParsedString::parse(source)?;
+                    return Ok(());
+                }
+            };
+        for f in &mut region.fragments {
+            f.pos_begin += region_begin;
+            f.pos_end += region_begin;
+        }
+        for f in &mut self.fragments[last + 1..] {
+            f.pos_begin = f.pos_begin + new_len - old_len;
+            f.pos_end = f.pos_end + new_len - old_len;
+        }
+        self.fragments.splice(first..=last, region.fragments);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cmd_ok() {
+        assert_eq!(
+            FragmentContent::parse("{}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from(""),
+                case: None
+            }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1 "" b}"##),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: None,
-                choices: vec![String::from(""), String::from("b")]
+            FragmentContent::parse("{{}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from("{"),
+                case: None
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1 "a b" "c"}"##),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: None,
-                choices: vec![String::from("a b"), String::from("c")]
+            FragmentContent::parse("{BIG_FONT}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from("BIG_FONT"),
+                case: None
             }))
         );
         assert_eq!(
-            FragmentContent::parse("{P 1:2 a b}"),
-            Ok(FragmentContent::Choice(ChoiceList {
-                name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b")]
+            FragmentContent::parse("{NUM}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from("NUM"),
+                case: None
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{1:RED}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: Some(1),
+                name: String::from("RED"),
+                case: None
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{STRING.gen}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from("STRING"),
+                case: Some(String::from("gen"))
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{1:STRING.gen}", &ParseOptions::default()),
+            Ok(FragmentContent::Command(StringCommand {
+                index: Some(1),
+                name: String::from("STRING"),
+                case: Some(String::from("gen"))
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{G=n}", &ParseOptions::default()),
+            Ok(FragmentContent::Gender(GenderDefinition {
+                gender: Some(String::from("n")),
+                indexref: None,
+                whitespace: None
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{G = n}", &ParseOptions::default()),
+            Ok(FragmentContent::Gender(GenderDefinition {
+                gender: Some(String::from("n")),
+                indexref: None,
+                whitespace: None
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1:2 "" b}"##),
+            FragmentContent::parse("{P a b}", &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: Some(2),
-                choices: vec![String::from(""), String::from("b")]
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1:2 "a b" "c"}"##),
+            FragmentContent::parse("{P\na\tb}", &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
-                indexref: Some(1),
-                indexsubref: Some(2),
-                choices: vec![String::from("a b"), String::from("c")]
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }))
         );
-
         assert_eq!(
-            FragmentContent::parse("{P a b c}"),
+            FragmentContent::parse(r##"{P "" b}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from(""), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P "" "" b}"##),
+            FragmentContent::parse(r##"{P "a b" "c"}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from("a b"), String::from("c")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P a ""}"##),
+            FragmentContent::parse("{P 1 a b}", &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
-                indexref: None,
+                indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse("{P 1 a b c}"),
+            FragmentContent::parse("{P\t1\na\rb\n}", &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1 "" "" b}"##),
+            FragmentContent::parse(r##"{P 1 "" b}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1 a ""}"##),
+            FragmentContent::parse(r##"{P 1 "a b" "c"}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a b"), String::from("c")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse("{P 1:2 a b c}"),
+            FragmentContent::parse("{P 1:2 a b}", &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1:2 "" "" b}"##),
+            FragmentContent::parse(r##"{P 1:2 "" b}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from("b")],
+                spans: Vec::new(),
             }))
         );
         assert_eq!(
-            FragmentContent::parse(r##"{P 1:2 a ""}"##),
+            FragmentContent::parse(r##"{P 1:2 "a b" "c"}"##, &ParseOptions::default()),
             Ok(FragmentContent::Choice(ChoiceList {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a b"), String::from("c")],
+                spans: Vec::new(),
             }))
         );
-    }
-
-    #[test]
-    fn test_parse_cmd_err() {
-        assert!(FragmentContent::parse("{1}").is_err());
-        assert!(FragmentContent::parse("{1:1}").is_err());
-        assert!(FragmentContent::parse("{1:1 NUM}").is_err());
-        assert!(FragmentContent::parse("{NUM=a}").is_err());
-        assert!(FragmentContent::parse(r##"{P " a}"##).is_err());
-        assert!(FragmentContent::parse(r##"{P 1.a a b}"##).is_err());
-        assert!(FragmentContent::parse(r##"{P 1:a a b}"##).is_err());
-    }
+
+        assert_eq!(
+            FragmentContent::parse("{P a b c}", &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P "" "" b}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P a ""}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{P 1 a b c}", &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P 1 "" "" b}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: None,
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P 1 a ""}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{P 1:2 a b c}", &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: Some(2),
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P 1:2 "" "" b}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: Some(2),
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P 1:2 a ""}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: Some(1),
+                indexsubref: Some(2),
+                choices: vec![String::from("a"), String::from("")],
+                spans: Vec::new(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let lower = ParseOptions::with_command_name("[a-z]+").unwrap();
+        assert!(FragmentContent::parse("{num}", &ParseOptions::default()).is_err());
+        assert_eq!(
+            FragmentContent::parse("{1:num.gen}", &lower),
+            Ok(FragmentContent::Command(StringCommand {
+                index: Some(1),
+                name: String::from("num"),
+                case: Some(String::from("gen"))
+            }))
+        );
+        assert!(FragmentContent::parse("{NUM}", &lower).is_err());
+        assert!(FragmentContent::parse("{}", &lower).is_ok());
+        assert!(FragmentContent::parse("{P a b}", &lower).is_ok());
+
+        assert!(ParsedString::parse("a {num} b").is_err());
+        let case1 = ParsedString::parse_with_options("a {num} b", &lower).unwrap();
+        assert_eq!(case1.compile(), "a {num} b");
+
+        assert!(ParseOptions::with_command_name("[a-z").is_err());
+    }
+
+    #[test]
+    fn test_parse_choice_item_quotes() {
+        assert_eq!(
+            FragmentContent::parse(r##"{P "say \"hi\"" x}"##, &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 8,
+                pos_end: Some(10),
+                byte_begin: 8,
+                byte_end: Some(10),
+                message: String::from("Quotes cannot be escaped in choice items."),
+            })
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P "a\" b}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a\\"), String::from("b")],
+                spans: Vec::new(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_choice_item_trailing_space() {
+        assert_eq!(
+            FragmentContent::parse(r##"{P "a" "b "  }"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a"), String::from("b ")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P " a b " " "}"##, &ParseOptions::default()),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from(" a b "), String::from(" ")],
+                spans: Vec::new(),
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse(r##"{P "a" "b"x}"##, &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 10,
+                pos_end: Some(11),
+                byte_begin: 10,
+                byte_end: Some(11),
+                message: String::from(
+                    "Choice items must be separated by whitespace, found text after closing quote."
+                ),
+            })
+        );
+        assert!(FragmentContent::parse(r##"{P "a" "b }"##, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_choice_reversed_quotes() {
+        assert_eq!(
+            FragmentContent::parse(r##"{P a" "b}"##, &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 4,
+                pos_end: Some(5),
+                byte_begin: 4,
+                byte_end: Some(5),
+                message: String::from(
+                    "Mismatched quotes in choice items, quotes must enclose a whole item."
+                ),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse(r##"x {P "a" b"c}"##).err(),
+            Some(ParserError {
+                pos_begin: 10,
+                pos_end: Some(11),
+                byte_begin: 10,
+                byte_end: Some(11),
+                message: String::from(
+                    "Mismatched quotes in choice items, quotes must enclose a whole item."
+                ),
+            })
+        );
+        assert!(FragmentContent::parse(r##"{P "a" "b"}"##, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_comment() {
+        assert_eq!(
+            FragmentContent::parse("{# note}", &ParseOptions::default()),
+            Ok(FragmentContent::Comment(String::from(" note")))
+        );
+        assert_eq!(
+            FragmentContent::parse("{#}", &ParseOptions::default()),
+            Ok(FragmentContent::Comment(String::from("")))
+        );
+        assert_eq!(
+            FragmentContent::Comment(String::from(" note")).compile(),
+            "{# note}"
+        );
+
+        let mut case1 =
+            ParsedString::parse("{# keep it short}{RED}Hello{# \"world\"} world").unwrap();
+        assert_eq!(case1.fragments.len(), 5);
+        case1.strip_comments();
+        assert_eq!(case1, ParsedString::parse("{RED}Hello world").unwrap());
+
+        let case2 = ParsedString::parse("{# use {RED} here}x").unwrap();
+        assert_eq!(
+            case2.fragments[0].content,
+            FragmentContent::Comment(String::from(" use {RED} here"))
+        );
+        assert_eq!(case2.fragments[0].pos_end, 18);
+        assert_eq!(case2.compile(), "{# use {RED} here}x");
+        assert_eq!(
+            ParsedString::parse("x{# {RED}").err(),
+            Some(ParserError {
+                pos_begin: 1,
+                pos_end: None,
+                byte_begin: 1,
+                byte_end: None,
+                message: String::from("Unterminated comment, '}' expected."),
+            })
+        );
+    }
+
+    #[test]
+    fn test_raw_command() {
+        let source = "Ünïcödé{G = n}{P  a b}";
+        let case1 = ParsedString::parse(source).unwrap();
+        assert_eq!(case1.fragments[0].raw_command(source), None);
+        assert_eq!(case1.fragments[1].raw_command(source), Some("{G = n}"));
+        assert_eq!(case1.fragments[1].content.compile(), "{G=n}");
+        assert_eq!(case1.fragments[2].raw_command(source), Some("{P  a b}"));
+        assert_eq!(case1.fragments[2].raw_command("{G}"), None);
+    }
+
+    #[test]
+    fn test_parse_gender_ref() {
+        assert_eq!(
+            FragmentContent::parse("{G=1}", &ParseOptions::default()),
+            Ok(FragmentContent::Gender(GenderDefinition {
+                gender: None,
+                indexref: Some(1),
+                whitespace: None
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{G=n1}", &ParseOptions::default()),
+            Ok(FragmentContent::Gender(GenderDefinition {
+                gender: Some(String::from("n1")),
+                indexref: None,
+                whitespace: None
+            }))
+        );
+
+        let case1 = ParsedString::parse("{G = 0}{STRING} und {G=n}").unwrap();
+        assert_eq!(case1.compile(), "{G=0}{STRING} und {G=n}");
+        assert_eq!(
+            ParsedString::parse(&case1.compile()).unwrap().compile(),
+            case1.compile()
+        );
+    }
+
+    #[test]
+    fn test_parse_gender_whitespace() {
+        let case1 = ParsedString::parse("{G = n}Text").unwrap();
+        assert_eq!(case1.compile(), "{G=n}Text");
+
+        let options = ParseOptions::default().with_gender_whitespace();
+        let case2 = ParsedString::parse_with_options("{G = n}Text", &options).unwrap();
+        assert_eq!(case2.compile(), "{G = n}Text");
+        assert_eq!(
+            case2.fragments[0].content,
+            FragmentContent::Gender(GenderDefinition {
+                gender: Some(String::from("n")),
+                indexref: None,
+                whitespace: Some((String::from(" "), String::from(" "))),
+            })
+        );
+
+        let case3 = ParsedString::parse_with_options("{G  =0}{G=f}", &options).unwrap();
+        assert_eq!(case3.compile(), "{G  =0}{G=f}");
+    }
+
+    #[test]
+    fn test_parse_string_ref() {
+        assert_eq!(
+            FragmentContent::parse("{STRING:STR_TOWN_NAME}", &ParseOptions::default()),
+            Ok(FragmentContent::StringRef {
+                id: String::from("STR_TOWN_NAME")
+            })
+        );
+        assert!(FragmentContent::parse("{STRING:}", &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse("{STRING:str}", &ParseOptions::default()).is_err());
+
+        let case1 = ParsedString::parse("In {STRING:STR_TOWN_NAME}: {NUM}").unwrap();
+        assert_eq!(case1.compile(), "In {STRING:STR_TOWN_NAME}: {NUM}");
+        assert_eq!(
+            case1.tree_string(),
+            "0..3 Text \"In \"\n\
+             3..25 StringRef STR_TOWN_NAME\n\
+             25..27 Text \": \"\n\
+             27..32 Command {NUM}\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_cmd_err() {
+        assert!(FragmentContent::parse("{1}", &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse("{1:1}", &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse("{1:1 NUM}", &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse("{NUM=a}", &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse(r##"{P " a}"##, &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse(r##"{P 1.a a b}"##, &ParseOptions::default()).is_err());
+        assert!(FragmentContent::parse(r##"{P 1:a a b}"##, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_choice_item_braces() {
+        assert_eq!(
+            FragmentContent::parse("{P a{ b}", &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 3,
+                pos_end: Some(5),
+                byte_begin: 3,
+                byte_end: Some(5),
+                message: String::from(
+                    "Invalid choice item 'a{', braces are only allowed as part of a string command."
+                ),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse("x {P 1 a{ b}").err(),
+            Some(ParserError {
+                pos_begin: 7,
+                pos_end: Some(9),
+                byte_begin: 7,
+                byte_end: Some(9),
+                message: String::from(
+                    "Invalid choice item 'a{', braces are only allowed as part of a string command."
+                ),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_choice_quoted_braces() {
+        let case1 = ParsedString::parse(r#"{P a "b}c"}"#).unwrap();
+        assert_eq!(
+            case1.fragments,
+            vec![StringFragment {
+                pos_begin: 0,
+                pos_end: 11,
+                content: FragmentContent::Choice(ChoiceList {
+                    name: String::from("P"),
+                    indexref: None,
+                    indexsubref: None,
+                    choices: vec![String::from("a"), String::from("b}c")],
+                    spans: Vec::new(),
+                }),
+            }]
+        );
+        assert_eq!(case1.compile(), r#"{P a "b}c"}"#);
+
+        let case2 = ParsedString::parse(r#"x {P 1 "a {" b} {NUM}"#).unwrap();
+        assert_eq!(case2.fragments.len(), 4);
+        assert_eq!(case2.compile(), r#"x {P 1 "a {" b} {NUM}"#);
+
+        // unpaired quotes fall back to the first closing brace
+        assert_eq!(
+            ParsedString::parse(r#"{P a" b}"#).err().map(|e| e.message),
+            Some(String::from(
+                "Mismatched quotes in choice items, quotes must enclose a whole item."
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_choice_unknown_prefix() {
+        assert_eq!(
+            FragmentContent::parse("{Q a b}", &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 1,
+                pos_end: Some(2),
+                byte_begin: 1,
+                byte_end: Some(2),
+                message: String::from("Unknown choice list 'Q', valid prefixes are 'P' and 'G'."),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse("x {Q 1 a b}").err(),
+            Some(ParserError {
+                pos_begin: 3,
+                pos_end: Some(4),
+                byte_begin: 3,
+                byte_end: Some(4),
+                message: String::from("Unknown choice list 'Q', valid prefixes are 'P' and 'G'."),
+            })
+        );
+        assert_eq!(
+            FragmentContent::parse("{QQ a b}", &ParseOptions::default()),
+            Err(ParserError {
+                pos_begin: 0,
+                pos_end: Some(8),
+                byte_begin: 0,
+                byte_end: Some(8),
+                message: String::from("Invalid string command: '{QQ a b}'"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_choice_nested() {
+        // choice items are never parsed recursively; nested choice lists are rejected outright
+        assert_eq!(
+            ParsedString::parse("{P {P a b} c}").err(),
+            Some(ParserError {
+                pos_begin: 3,
+                pos_end: Some(5),
+                byte_begin: 3,
+                byte_end: Some(5),
+                message: String::from(
+                    "Invalid choice item '{P', braces are only allowed as part of a string command."
+                ),
+            })
+        );
+
+        let deep = format!("{}a{}", "{P x ".repeat(10000), "}".repeat(10000));
+        assert!(ParsedString::parse(&deep).is_err());
+    }
+
+    #[test]
+    fn test_set_choices() {
+        let mut case1 = ParsedString::parse("{NUM} {P bus} in {STRING}").unwrap();
+        let FragmentContent::Choice(choice) = &mut case1.fragments[2].content else {
+            panic!("Expected a choice list");
+        };
+        let items = vec![
+            String::from("bus"),
+            String::from("busses"),
+            String::from(""),
+        ];
+        assert_eq!(choice.set_choices(items), Ok(()));
+        case1.update_positions();
+        assert_eq!(case1.compile(), "{NUM} {P bus busses \"\"} in {STRING}");
+        assert_eq!(case1, ParsedString::parse(&case1.compile()).unwrap());
+
+        let FragmentContent::Choice(choice) = &mut case1.fragments[2].content else {
+            panic!("Expected a choice list");
+        };
+        assert!(choice.set_choices(vec![]).is_err());
+        assert!(choice.set_choices(vec![String::from("a\"b")]).is_err());
+        assert!(choice.set_choices(vec![String::from("a{")]).is_err());
+        assert!(choice.set_choices(vec![String::from("{TRAIN}")]).is_ok());
+    }
 
     #[test]
     fn test_compile_cmd() {
@@ -534,7 +2253,9 @@ mod tests {
         );
         assert_eq!(
             GenderDefinition {
-                gender: String::from("n")
+                gender: Some(String::from("n")),
+                indexref: None,
+                whitespace: None
             }
             .compile(),
             "{G=n}"
@@ -544,7 +2265,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }
             .compile(),
             "{P a b}"
@@ -554,7 +2276,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(" b")]
+                choices: vec![String::from(""), String::from(" b")],
+                spans: Vec::new(),
             }
             .compile(),
             r##"{P "" " b"}"##
@@ -564,7 +2287,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }
             .compile(),
             "{P 1 a b}"
@@ -574,13 +2298,49 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                spans: Vec::new(),
             }
             .compile(),
             "{P 1:2 a b}"
         );
     }
 
+    #[test]
+    fn test_sort_cmd() {
+        let cmd = |index: Option<usize>, name: &str, case: Option<&str>| StringCommand {
+            index,
+            name: String::from(name),
+            case: case.map(String::from),
+        };
+        let mut commands = vec![
+            cmd(Some(1), "STRING", None),
+            cmd(None, "STRING", Some("gen")),
+            cmd(Some(0), "NUM", None),
+            cmd(None, "STRING", None),
+            cmd(Some(1), "STRING", Some("dat")),
+            cmd(None, "COMMA", None),
+        ];
+        commands.sort();
+        assert_eq!(
+            commands,
+            vec![
+                cmd(None, "COMMA", None),
+                cmd(Some(0), "NUM", None),
+                cmd(None, "STRING", None),
+                cmd(None, "STRING", Some("gen")),
+                cmd(Some(1), "STRING", None),
+                cmd(Some(1), "STRING", Some("dat")),
+            ]
+        );
+
+        let bag: std::collections::HashSet<StringCommand> = commands
+            .into_iter()
+            .map(|c| StringCommand { index: None, ..c })
+            .collect();
+        assert_eq!(bag.len(), 5);
+    }
+
     #[test]
     fn test_parse_str_empty() {
         let case1 = ParsedString::parse("");
@@ -603,7 +2363,9 @@ mod tests {
                     pos_begin: 0,
                     pos_end: 5,
                     content: FragmentContent::Gender(GenderDefinition {
-                        gender: String::from("n")
+                        gender: Some(String::from("n")),
+                        indexref: None,
+                        whitespace: None
                     })
                 },
                 StringFragment {
@@ -635,6 +2397,876 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_str_verbatim() {
+        let case1 = ParsedString::parse("{RED}See {RAW https://example.org/{id}?a={{b}}} now");
+        assert!(case1.is_ok());
+        let case1 = case1.unwrap();
+        assert_eq!(
+            case1.fragments[2],
+            StringFragment {
+                pos_begin: 9,
+                pos_end: 47,
+                content: FragmentContent::Verbatim(String::from(
+                    "https://example.org/{id}?a={{b}}"
+                ))
+            }
+        );
+        assert_eq!(
+            case1.compile(),
+            "{RED}See {RAW https://example.org/{id}?a={{b}}} now"
+        );
+
+        let case2 = ParsedString::parse("{RAW_STRING} {RAW {a}");
+        assert_eq!(
+            case2.err(),
+            Some(ParserError {
+                pos_begin: 13,
+                pos_end: None,
+                byte_begin: 13,
+                byte_end: None,
+                message: String::from("Unterminated verbatim region, '}' expected."),
+            })
+        );
+    }
+
+    #[test]
+    fn test_compiled_len() {
+        for string in [
+            "",
+            "Hello world",
+            "{G=n}{RED}{NUM} {P Zug \"Züge \"}{# note}",
+            "{RAW {P a b}}Ωμέγα{STRING:STR_TOWN}",
+        ] {
+            let parsed = ParsedString::parse(string).unwrap();
+            assert_eq!(parsed.compiled_len(), parsed.compile().chars().count());
+        }
+    }
+
+    #[test]
+    fn test_to_template() {
+        let case1 =
+            ParsedString::parse("{RED}Age: {LTBLUE}{STRING2}{BLACK} Cost: {CURRENCY_LONG}/year")
+                .unwrap();
+        assert_eq!(
+            case1.to_template(),
+            "{RED}{LTBLUE}{STRING2}{BLACK}{CURRENCY_LONG}"
+        );
+        assert_eq!(ParsedString::parse("Text").unwrap().to_template(), "");
+    }
+
+    #[test]
+    fn test_is_plain_text() {
+        assert!(ParsedString::parse("Hello world").unwrap().is_plain_text());
+        assert!(ParsedString::parse("").unwrap().is_plain_text());
+        assert!(!ParsedString::parse("Hello {NUM}").unwrap().is_plain_text());
+        assert!(!ParsedString::parse("{G=n}Hello").unwrap().is_plain_text());
+        assert!(!ParsedString::parse("Hello{# world}")
+            .unwrap()
+            .is_plain_text());
+    }
+
+    #[test]
+    fn test_fragments_in_range() {
+        let case1 = ParsedString::parse("{RED}Hello {NUM} world").unwrap();
+        let found: Vec<&StringFragment> = case1.fragments_in_range(8..13).collect();
+        assert_eq!(found, vec![&case1.fragments[1], &case1.fragments[2]]);
+        assert_eq!(case1.fragments_in_range(5..11).count(), 1);
+        assert_eq!(case1.fragments_in_range(0..100).count(), 4);
+        assert_eq!(case1.fragments_in_range(100..200).count(), 0);
+    }
+
+    #[test]
+    fn test_split_at_pos() {
+        let case1 = ParsedString::parse("{RED}Hello {NUM} world").unwrap();
+
+        let (first, second) = case1.split_at_pos(8);
+        assert_eq!(first.compile(), "{RED}Hel");
+        assert_eq!(second.compile(), "lo {NUM} world");
+        assert_eq!(second.fragments[1].pos_begin, 3);
+        assert_eq!(second.fragments[1].pos_end, 8);
+
+        let (first, second) = case1.split_at_pos(11);
+        assert_eq!(first.compile(), "{RED}Hello ");
+        assert_eq!(second.compile(), "{NUM} world");
+        assert_eq!(second.fragments.len(), 2);
+
+        let (first, second) = case1.split_at_pos(13);
+        assert_eq!(first.compile(), "{RED}Hello ");
+        assert_eq!(second.compile(), "{NUM} world");
+
+        let (first, second) = case1.split_at_pos(0);
+        assert_eq!(first.fragments, vec![]);
+        assert_eq!(second, case1);
+    }
+
+    #[test]
+    fn test_colored_runs() {
+        let case1 = ParsedString::parse("{RED}a{BLACK}b").unwrap();
+        assert_eq!(
+            case1.colored_runs(),
+            vec![
+                (String::from("a"), Some(String::from("RED"))),
+                (String::from("b"), Some(String::from("BLACK"))),
+            ]
+        );
+
+        let case2 = ParsedString::parse("a {NUM}{P b c}{PUSH_COLOUR}{RED}d{POP_COLOUR}e").unwrap();
+        assert_eq!(
+            case2.colored_runs(),
+            vec![
+                (String::from("a "), None),
+                (String::from("d"), Some(String::from("RED"))),
+                (String::from("e"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_concat_safe() {
+        assert!(!ParsedString::parse("{RED}text").unwrap().is_concat_safe());
+        assert!(ParsedString::parse("{RED}text{BLACK}")
+            .unwrap()
+            .is_concat_safe());
+        assert!(ParsedString::parse("text {NUM}").unwrap().is_concat_safe());
+        assert!(ParsedString::parse("{PUSH_COLOUR}{RED}text{POP_COLOUR}")
+            .unwrap()
+            .is_concat_safe());
+        assert!(!ParsedString::parse("{PUSH_COLOUR}{RED}text")
+            .unwrap()
+            .is_concat_safe());
+        assert!(!ParsedString::parse("text {G a b}")
+            .unwrap()
+            .is_concat_safe());
+        assert!(ParsedString::parse("{NUM} text {P a b}")
+            .unwrap()
+            .is_concat_safe());
+    }
+
+    #[test]
+    fn test_declared_gender() {
+        let case1 = ParsedString::parse("{G=f}Stadt").unwrap();
+        assert_eq!(case1.declared_gender(), Some("f"));
+        let case2 = ParsedString::parse("{G=m}{G=f}Stadt").unwrap();
+        assert_eq!(case2.declared_gender(), Some("m"));
+        let case3 = ParsedString::parse("Stadt").unwrap();
+        assert_eq!(case3.declared_gender(), None);
+    }
+
+    #[test]
+    fn test_try_compile() {
+        let mut case1 = ParsedString::parse("{RED}Hello {NUM}").unwrap();
+        assert_eq!(case1.try_compile(), Ok(String::from("{RED}Hello {NUM}")));
+
+        case1.fragments.push(StringFragment {
+            pos_begin: 16,
+            pos_end: 21,
+            content: FragmentContent::Command(StringCommand {
+                index: None,
+                name: String::from("num"),
+                case: None,
+            }),
+        });
+        assert_eq!(case1.compile(), "{RED}Hello {NUM}{num}");
+        assert_eq!(
+            case1.try_compile(),
+            Err(ParserError {
+                pos_begin: 16,
+                pos_end: Some(21),
+                byte_begin: 16,
+                byte_end: Some(21),
+                message: String::from("Invalid string command name 'num'."),
+            })
+        );
+    }
+
+    #[test]
+    fn test_tree_string() {
+        let case1 =
+            ParsedString::parse("{G=n}{RED}{1:STRING.gen} und {P 0:1 \"ein Bus\" Busse}").unwrap();
+        assert_eq!(
+            case1.tree_string(),
+            "0..5 Gender n\n\
+             5..10 Command {RED}\n\
+             10..24 Command {1:STRING.gen}\n\
+             24..29 Text \" und \"\n\
+             29..52 Choice P 0:1\n  \
+             0: \"ein Bus\"\n  \
+             1: \"Busse\"\n"
+        );
+    }
+
+    #[test]
+    fn test_signature_string() {
+        let case1 = ParsedString::parse(
+            "{G=n}{STRING.gen} {RED}{G a b c} {NUM} {P \"ein Bus\" Busse}{BLACK}",
+        )
+        .unwrap();
+        assert_eq!(
+            case1.signature_string(),
+            "BLACK,NUM,RED,STRING.gen,G[3],P[2]"
+        );
+
+        let case2 = ParsedString::parse("Text").unwrap();
+        assert_eq!(case2.signature_string(), "");
+    }
+
+    #[test]
+    fn test_accept() {
+        #[derive(Default)]
+        struct Counter {
+            texts: usize,
+            commands: Vec<String>,
+            genders: usize,
+            choices: usize,
+        }
+
+        impl Visitor for Counter {
+            fn visit_text(&mut self, _text: &str) {
+                self.texts += 1;
+            }
+            fn visit_command(&mut self, command: &StringCommand) {
+                self.commands.push(command.name.clone());
+            }
+            fn visit_gender(&mut self, _gender: &GenderDefinition) {
+                self.genders += 1;
+            }
+            fn visit_choice(&mut self, _choice: &ChoiceList) {
+                self.choices += 1;
+            }
+        }
+
+        let case1 = ParsedString::parse("{G=n}{RED}{NUM} {P Bus Busse}{# note}").unwrap();
+        let mut counter = Counter::default();
+        case1.accept(&mut counter);
+        assert_eq!(counter.texts, 3);
+        assert_eq!(counter.commands, vec!["RED", "NUM"]);
+        assert_eq!(counter.genders, 1);
+        assert_eq!(counter.choices, 1);
+
+        let choice = FragmentContent::parse("{P {TRAIN} {SHIP}}", &ParseOptions::default());
+        let case2 = ParsedString {
+            fragments: vec![StringFragment {
+                pos_begin: 0,
+                pos_end: 18,
+                content: choice.unwrap(),
+            }],
+        };
+        let mut counter = Counter::default();
+        case2.accept(&mut counter);
+        assert_eq!(counter.texts, 0);
+        assert_eq!(counter.commands, vec!["TRAIN", "SHIP"]);
+        assert_eq!(counter.choices, 1);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let result = ParsedString::parse_lines("{RED}Hello\nWorld {NUM\n");
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], Ok(ParsedString::parse("{RED}Hello").unwrap()));
+        assert_eq!(
+            result[1],
+            Err(LineError {
+                line: 2,
+                error: ParserError {
+                    pos_begin: 6,
+                    pos_end: None,
+                    byte_begin: 6,
+                    byte_end: None,
+                    message: String::from("Unterminated string command, '}' expected."),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_all_branches() {
+        let case1 = ParsedString::parse("{G=n}{NUM} {P Zug Züge} {G der die das}").unwrap();
+        assert_eq!(
+            case1.render_all_branches(),
+            Ok(vec![
+                String::from("{NUM} Zug der"),
+                String::from("{NUM} Zug die"),
+                String::from("{NUM} Zug das"),
+                String::from("{NUM} Züge der"),
+                String::from("{NUM} Züge die"),
+                String::from("{NUM} Züge das"),
+            ])
+        );
+
+        let case2 = ParsedString::parse("Text").unwrap();
+        assert_eq!(case2.render_all_branches(), Ok(vec![String::from("Text")]));
+
+        let case3 = ParsedString::parse(&"{P a b c d}".repeat(5)).unwrap();
+        assert_eq!(
+            case3.render_all_branches(),
+            Err(ParserError {
+                pos_begin: 44,
+                pos_end: Some(55),
+                byte_begin: 44,
+                byte_end: Some(55),
+                message: String::from(
+                    "Too many combinations of choices, at most 256 are rendered."
+                ),
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_branches() {
+        let case1 = ParsedString::parse("{G=n}{NUM} {P Zug Züge} {G der die das}").unwrap();
+        let selections = BranchSelections::from([(0, 1), (1, 2)]);
+        assert_eq!(
+            case1.select_branches(&selections),
+            ParsedString::parse("{NUM} Züge das")
+        );
+
+        let selections = BranchSelections::from([(0, 1)]);
+        assert_eq!(
+            case1.select_branches(&selections),
+            Err(ParserError {
+                pos_begin: 24,
+                pos_end: Some(39),
+                byte_begin: 25,
+                byte_end: Some(40),
+                message: String::from("No valid selection for choice list 1."),
+            })
+        );
+
+        let selections = BranchSelections::from([(0, 2), (1, 0)]);
+        assert!(case1.select_branches(&selections).is_err());
+    }
+
+    #[test]
+    fn test_choice_item_spans() {
+        let choice =
+            FragmentContent::parse(r#"{P 1:2 car "" "two cars"}"#, &ParseOptions::default());
+        if let Ok(FragmentContent::Choice(choice)) = choice {
+            assert_eq!(choice.item_spans(), vec![(7, 10), (12, 12), (15, 23)]);
+        } else {
+            panic!("Expected a choice list");
+        }
+
+        let case1 = ParsedString::parse(r#"x {P car "" "two cars"}"#).unwrap();
+        assert_eq!(
+            case1.fragments[1].choice_item_spans(),
+            Some(vec![(5, 8), (10, 10), (13, 21)])
+        );
+        let mut case2 = ParsedString::parse(r#"ä {P "car" ""  cars}"#).unwrap();
+        assert_eq!(
+            case2.fragments[1].choice_item_spans(),
+            Some(vec![(6, 9), (12, 12), (15, 19)])
+        );
+        assert_eq!(case2.fragments[0].choice_item_spans(), None);
+        case2.update_positions();
+        assert_eq!(
+            case2.fragments[1].choice_item_spans(),
+            Some(vec![(5, 8), (10, 10), (12, 16)])
+        );
+
+        let mut case3 = ParsedString::parse("x {P a b}").unwrap();
+        if let FragmentContent::Choice(choice) = &mut case3.fragments[1].content {
+            choice
+                .set_choices(vec![String::from("car"), String::from("cars")])
+                .unwrap();
+        }
+        assert_eq!(case3.fragments[1].choice_item_spans(), None);
+        case3.update_positions();
+        assert_eq!(
+            case3.fragments[1].choice_item_spans(),
+            Some(vec![(5, 8), (9, 13)])
+        );
+    }
+
+    #[test]
+    fn test_choice_normalize_quoting() {
+        let mut choice = ChoiceList {
+            name: String::from("P"),
+            indexref: None,
+            indexsubref: None,
+            choices: vec![
+                String::from("\"car\""),
+                String::from("\"two cars\""),
+                String::from("\"\""),
+                String::from("cars"),
+            ],
+            spans: Vec::new(),
+        };
+        choice.normalize_quoting();
+        assert_eq!(choice.choices, vec!["car", "two cars", "", "cars"]);
+        assert_eq!(choice.compile(), r#"{P car "two cars" "" cars}"#);
+
+        let parsed = FragmentContent::parse(r#"{P "car" "two cars"}"#, &ParseOptions::default());
+        if let Ok(FragmentContent::Choice(mut choice)) = parsed {
+            choice.normalize_quoting();
+            assert_eq!(choice.compile(), r#"{P car "two cars"}"#);
+        } else {
+            panic!("Expected a choice list");
+        }
+    }
+
+    #[test]
+    fn test_worst_case_length() {
+        let case1 = ParsedString::parse("{G=n}{RED}{NUM} {P Wagen Wagenladungen}{NBSP}").unwrap();
+        let FragmentContent::Choice(choice) = &case1.fragments[4].content else {
+            panic!("Expected a choice list");
+        };
+        assert_eq!(choice.max_item_len(), 13);
+        assert_eq!(case1.worst_case_length(), 5 + 1 + 13 + 1);
+
+        let choice = FragmentContent::parse("{P {NBSP} {STRING}}", &ParseOptions::default());
+        let Ok(FragmentContent::Choice(choice)) = choice else {
+            panic!("Expected a choice list");
+        };
+        assert_eq!(choice.max_item_len(), 8);
+
+        assert_eq!(ParsedString::parse("").unwrap().worst_case_length(), 0);
+    }
+
+    #[test]
+    fn test_parse_with_leading_comment() {
+        assert_eq!(
+            ParsedString::parse_with_leading_comment(
+                "// Shown in the vehicle list\n#Keep it short\n{NUM} trains"
+            ),
+            Ok((
+                Some(String::from("Shown in the vehicle list\nKeep it short")),
+                ParsedString::parse("{NUM} trains").unwrap()
+            ))
+        );
+        assert_eq!(
+            ParsedString::parse_with_leading_comment("# 1 train"),
+            Ok((None, ParsedString::parse("# 1 train").unwrap()))
+        );
+        assert_eq!(
+            ParsedString::parse_with_leading_comment("// note\n{NUM"),
+            Err(ParserError {
+                pos_begin: 0,
+                pos_end: None,
+                byte_begin: 0,
+                byte_end: None,
+                message: String::from("Unterminated string command, '}' expected."),
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_byte_positions() {
+        let err = ParsedString::parse("Ünïcödé {NUM.}").unwrap_err();
+        assert_eq!((err.pos_begin, err.pos_end), (8, Some(14)));
+        assert_eq!((err.byte_begin, err.byte_end), (12, Some(18)));
+
+        let err = ParsedString::parse("Ünïcödé {NUM").unwrap_err();
+        assert_eq!((err.pos_begin, err.pos_end), (8, None));
+        assert_eq!((err.byte_begin, err.byte_end), (12, None));
+
+        let err = ParsedString::parse("Ünïcödé {P ü\"ß\"}").unwrap_err();
+        assert_eq!((err.pos_begin, err.pos_end), (12, Some(13)));
+        assert_eq!((err.byte_begin, err.byte_end), (17, Some(18)));
+
+        let err = ParsedString::parse("{RAW é}{P 1 \"éé\\\"x y}").unwrap_err();
+        assert_eq!((err.pos_begin, err.pos_end), (15, Some(17)));
+        assert_eq!((err.byte_begin, err.byte_end), (18, Some(20)));
+    }
+
+    #[test]
+    fn test_matching_brace() {
+        let source = "{RED}Ünïcödé {RAW {a {b}} c}";
+        assert_eq!(matching_brace(source, 0), Some(4));
+        assert_eq!(matching_brace(source, 13), Some(27));
+        assert_eq!(matching_brace(source, 18), Some(24));
+        assert_eq!(matching_brace(source, 1), None);
+
+        let source = "a{{}b{P {{} x}";
+        assert_eq!(matching_brace(source, 1), Some(3));
+        assert_eq!(matching_brace(source, 5), Some(13));
+
+        assert_eq!(matching_brace("{NUM", 0), None);
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(
+            ParsedString::parse_bytes("{RED}\u{039f}\u{03c0}".as_bytes()),
+            Ok(ParsedString::parse("{RED}\u{039f}\u{03c0}").unwrap())
+        );
+        assert_eq!(
+            ParsedString::parse_bytes(b"{RED}\xce\x9f\xff{NUM}").err(),
+            Some(ParserError {
+                pos_begin: 6,
+                pos_end: Some(7),
+                byte_begin: 7,
+                byte_end: Some(8),
+                message: String::from("Invalid UTF-8 sequence at byte 7."),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse_bytes(b"{RED}\xce").err(),
+            Some(ParserError {
+                pos_begin: 5,
+                pos_end: None,
+                byte_begin: 5,
+                byte_end: None,
+                message: String::from("Invalid UTF-8 sequence at byte 5."),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse_bytes("\u{039f}{FOO BAR}".as_bytes()).err(),
+            Some(ParserError {
+                pos_begin: 1,
+                pos_end: Some(10),
+                byte_begin: 2,
+                byte_end: Some(11),
+                message: String::from("Invalid string command: '{FOO BAR}'"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let case1 =
+            ParsedString::parse("{RED}{NUM} {2:STRING}{FOOBAR}{COMMA}{P a b}{0:CARGO_LONG}")
+                .unwrap();
+        assert_eq!(
+            case1.parameters(),
+            vec![
+                ParameterSlot {
+                    index: 0,
+                    name: String::from("NUM"),
+                    pos_begin: 5,
+                    pos_end: 10
+                },
+                ParameterSlot {
+                    index: 2,
+                    name: String::from("STRING"),
+                    pos_begin: 11,
+                    pos_end: 21
+                },
+                ParameterSlot {
+                    index: 3,
+                    name: String::from("COMMA"),
+                    pos_begin: 29,
+                    pos_end: 36
+                },
+                ParameterSlot {
+                    index: 0,
+                    name: String::from("CARGO_LONG"),
+                    pos_begin: 43,
+                    pos_end: 57
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let case1 = ParsedString::parse("{G=n}{RED}Hello {1:STRING.gen}{P 1 a \"b c\"}").unwrap();
+        let json = serde_json::to_string(&case1).unwrap();
+        assert_eq!(ParsedString::from_json(&json), Ok(case1));
+
+        let case2 = ParsedString::from_json(
+            r#"{"fragments": [
+                {"pos_begin": 3, "pos_end": 1, "content": {"Command": {"index": null, "name": "RED", "case": null}}},
+                {"pos_begin": 0, "pos_end": 0, "content": {"Text": "Hello"}}
+            ]}"#,
+        );
+        assert_eq!(case2, ParsedString::parse("{RED}Hello"));
+
+        let case3 = ParsedString::from_json(
+            r#"{"fragments": [{"pos_begin": 0, "pos_end": 5, "content": {"Text": "{RED}"}}]}"#,
+        );
+        assert_eq!(
+            case3.err().map(|e| e.message),
+            Some(String::from("Fragments do not match their compiled form."))
+        );
+
+        let case4 = ParsedString::from_json(r#"{"fragments": [}"#);
+        assert!(case4.err().unwrap().message.starts_with("Invalid JSON: "));
+
+        let options = ParseOptions::default().with_gender_whitespace();
+        let case5 = ParsedString::parse_with_options("{G = n}Zug", &options).unwrap();
+        let json = serde_json::to_string(&case5).unwrap();
+        assert_eq!(
+            ParsedString::from_json_with_options(&json, &options),
+            Ok(case5)
+        );
+        assert!(ParsedString::from_json(&json).is_err());
+
+        let options = ParseOptions::with_command_name("[a-z]+").unwrap();
+        let case6 = ParsedString::parse_with_options("{red}Hello", &options).unwrap();
+        let json = serde_json::to_string(&case6).unwrap();
+        assert_eq!(
+            ParsedString::from_json_with_options(&json, &options),
+            Ok(case6)
+        );
+    }
+
+    #[test]
+    fn test_fix_trailing_whitespace() {
+        let base = ParsedString::parse("{RED}Hello {STRING} ").unwrap();
+
+        let mut case1 = ParsedString::parse("{RED}Hallo {STRING}").unwrap();
+        assert!(case1.fix_trailing_whitespace(&base));
+        assert_eq!(case1, ParsedString::parse("{RED}Hallo {STRING} ").unwrap());
+        assert!(!case1.fix_trailing_whitespace(&base));
+
+        let mut case2 = ParsedString::parse("{RED}Hallo\u{039f}\t\n").unwrap();
+        assert!(case2.fix_trailing_whitespace(&base));
+        assert_eq!(case2, ParsedString::parse("{RED}Hallo\u{039f} ").unwrap());
+
+        let base = ParsedString::parse("{RED}Hello").unwrap();
+        let mut case3 = ParsedString::parse("{RED}  ").unwrap();
+        assert!(case3.fix_trailing_whitespace(&base));
+        assert_eq!(case3, ParsedString::parse("{RED}").unwrap());
+    }
+
+    #[test]
+    fn test_indexed_commands() {
+        let case1 =
+            ParsedString::parse("{NUM}{RED}{STRING}{5:COMMA}{BLUE}{CARGO_LONG}{1:STRING}").unwrap();
+        let indices: Vec<(usize, &str)> = case1
+            .indexed_commands()
+            .map(|(i, c)| (i, c.name.as_str()))
+            .collect();
+        assert_eq!(
+            indices,
+            vec![
+                (0, "NUM"),
+                (1, "STRING"),
+                (5, "COMMA"),
+                (6, "CARGO_LONG"),
+                (1, "STRING")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_required_commands() {
+        let case1 =
+            ParsedString::parse("{RED}{TINY_FONT}{NUM} {NBSP}{1:STRING.gen}{BLUE}").unwrap();
+        assert_eq!(
+            case1.required_commands(),
+            vec![
+                StringCommand {
+                    index: None,
+                    name: String::from("NUM"),
+                    case: None
+                },
+                StringCommand {
+                    index: Some(1),
+                    name: String::from("STRING"),
+                    case: Some(String::from("gen"))
+                },
+            ]
+        );
+        assert_eq!(
+            ParsedString::parse("{RED}Text")
+                .unwrap()
+                .required_commands(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_parameter_spans() {
+        let case1 = ParsedString::parse("{RED}{NUM} {TINY_FONT}{STRING}{}{1:COMMA}{FOO}").unwrap();
+        assert_eq!(case1.parameter_spans(), vec![(5, 10), (22, 30), (32, 41)]);
+
+        let case2 = ParsedString::parse("{BLACK}Text").unwrap();
+        assert_eq!(case2.parameter_spans(), vec![]);
+    }
+
+    #[test]
+    fn test_completeness() {
+        let base = ParsedString::parse("{RED}{NUM} {STRING} built").unwrap();
+
+        let trans = ParsedString::parse("{RED}{NUM} {STRING} gebaut").unwrap();
+        assert_eq!(trans.completeness(&base), 1.0);
+        let trans = ParsedString::parse(" ").unwrap();
+        assert_eq!(trans.completeness(&base), 0.0);
+        let trans = ParsedString::parse("{RED}{NUM} gebaut").unwrap();
+        assert_eq!(trans.completeness(&base), 0.75);
+        let trans = ParsedString::parse("{RED}{NUM} {STRING}").unwrap();
+        assert_eq!(trans.completeness(&base), 0.75);
+        let trans = ParsedString::parse("{RED}{NUM} {STRING} {BLUE}gebaut").unwrap();
+        assert_eq!(trans.completeness(&base), 0.8);
+
+        let base = ParsedString::parse("{NUM}").unwrap();
+        let trans = ParsedString::parse("{NUM}").unwrap();
+        assert_eq!(trans.completeness(&base), 1.0);
+    }
+
+    #[test]
+    fn test_command_diff() {
+        let base = ParsedString::parse("{RED}{NUM} {STRING} {RED}{COMMA}").unwrap();
+        let trans = ParsedString::parse("{RED}{NUM} {BLUE}{STRING} {COMMA}").unwrap();
+        let (only_trans, only_base) = trans.command_diff(&base);
+        assert_eq!(
+            only_trans,
+            vec![StringCommand {
+                index: None,
+                name: String::from("BLUE"),
+                case: None
+            }]
+        );
+        assert_eq!(
+            only_base,
+            vec![StringCommand {
+                index: None,
+                name: String::from("RED"),
+                case: None
+            }]
+        );
+
+        let (only_base, only_trans) = base.command_diff(&base);
+        assert!(only_base.is_empty());
+        assert!(only_trans.is_empty());
+    }
+
+    #[test]
+    fn test_command_diff_with_case() {
+        let options = ParseOptions::with_command_name("[A-Za-z]+").unwrap();
+        let base = ParsedString::parse("{NUM} {STRING}").unwrap();
+        let trans = ParsedString::parse_with_options("{NUM} {string}", &options).unwrap();
+
+        let (only_trans, only_base) = trans.command_diff_with_case(&base, true);
+        assert_eq!(only_trans.len(), 1);
+        assert_eq!(only_trans[0].name, "string");
+        assert_eq!(only_base.len(), 1);
+        assert_eq!(only_base[0].name, "STRING");
+        assert_eq!(trans.command_diff(&base), (only_trans, only_base));
+
+        let (only_trans, only_base) = trans.command_diff_with_case(&base, false);
+        assert!(only_trans.is_empty());
+        assert!(only_base.is_empty());
+    }
+
+    #[test]
+    fn test_choice_refs() {
+        let case1 =
+            ParsedString::parse("{G a b}{NUM} {P a b} {2:STRING}{G c d}{P 0 x y}{RED}{P e f}")
+                .unwrap();
+        let refs: Vec<(&str, Option<usize>)> = case1
+            .choice_refs()
+            .into_iter()
+            .map(|(_, choice, ref_pos)| (choice.name.as_str(), ref_pos))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                ("G", Some(0)),
+                ("P", Some(0)),
+                ("G", Some(3)),
+                ("P", Some(0)),
+                ("P", Some(2)),
+            ]
+        );
+
+        let case2 = ParsedString::parse("{P a b} {FOO}").unwrap();
+        assert_eq!(case2.choice_refs()[0].2, None);
+    }
+
+    #[test]
+    fn test_plural_diff() {
+        let base = ParsedString::parse("{NUM} {P train trains}, {COMMA} {P car cars}").unwrap();
+
+        let trans = ParsedString::parse("{NUM} {P Zug Züge}, {COMMA} {P Wagen Wagen}").unwrap();
+        assert_eq!(trans.plural_diff(&base), vec![]);
+
+        let trans =
+            ParsedString::parse("{NUM} {P Zug Züge Züges}, {COMMA} {P Wagen Wagen}").unwrap();
+        assert_eq!(
+            trans.plural_diff(&base),
+            vec![PluralFinding::ItemCount {
+                list: 0,
+                base: 2,
+                found: 3
+            }]
+        );
+
+        let trans = ParsedString::parse("{1:COMMA} {P Wagen Wagen}, {0:NUM} {P Zug Züge}").unwrap();
+        assert_eq!(trans.plural_diff(&base), vec![PluralFinding::Reordered]);
+
+        let trans = ParsedString::parse("{NUM} {P Zug Züge}, {COMMA} {P 0 Wagen Wagen}").unwrap();
+        assert_eq!(
+            trans.plural_diff(&base),
+            vec![PluralFinding::Reference {
+                list: 1,
+                base: Some(1),
+                found: Some(0)
+            }]
+        );
+
+        let trans = ParsedString::parse("{NUM} {P Zug Züge}, {COMMA} Wagen").unwrap();
+        assert_eq!(
+            trans.plural_diff(&base),
+            vec![PluralFinding::Missing { list: 1 }]
+        );
+        assert_eq!(
+            base.plural_diff(&trans),
+            vec![PluralFinding::Extra { list: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_equals_ignoring_positions() {
+        let case1 = ParsedString::parse("{G=n}{RED}Hello {STRING}").unwrap();
+        let mut case2 = ParsedString::parse("{G = n}{RED}Hello {STRING}").unwrap();
+        assert_ne!(case1, case2);
+        assert!(case1.equals_ignoring_positions(&case2));
+        for f in &mut case2.fragments {
+            f.pos_begin += 10;
+            f.pos_end += 10;
+        }
+        assert!(case1.equals_ignoring_positions(&case2));
+
+        let case3 = ParsedString::parse("{G=n}{RED}Hello {NUM}").unwrap();
+        assert!(!case1.equals_ignoring_positions(&case3));
+        let case4 = ParsedString::parse("{G=n}{RED}Hello ").unwrap();
+        assert!(!case1.equals_ignoring_positions(&case4));
+    }
+
+    #[test]
+    fn test_reparse_range() {
+        let mut parsed = ParsedString::parse("{RED}Hello{STRING} world").unwrap();
+        let red = match &parsed.fragments[0].content {
+            FragmentContent::Command(cmd) => cmd.name.as_ptr(),
+            _ => panic!(),
+        };
+        let string = match &parsed.fragments[2].content {
+            FragmentContent::Command(cmd) => cmd.name.as_ptr(),
+            _ => panic!(),
+        };
+
+        let source = "{RED}Hello there{STRING} world";
+        assert!(parsed.reparse_range(source, 10..16).is_ok());
+        assert_eq!(parsed, ParsedString::parse(source).unwrap());
+        // command fragments are moved, not parsed again
+        match &parsed.fragments[0].content {
+            FragmentContent::Command(cmd) => assert_eq!(cmd.name.as_ptr(), red),
+            _ => panic!(),
+        };
+        match &parsed.fragments[2].content {
+            FragmentContent::Command(cmd) => assert_eq!(cmd.name.as_ptr(), string),
+            _ => panic!(),
+        };
+
+        let source = "{RED}Hello {NUM}there{STRING} world";
+        assert!(parsed.reparse_range(source, 11..16).is_ok());
+        assert_eq!(parsed, ParsedString::parse(source).unwrap());
+
+        let source = "{RED}Hello {NUMthere{STRING} world";
+        assert!(parsed.reparse_range(source, 14..15).is_err());
+        assert_eq!(
+            parsed,
+            ParsedString::parse("{RED}Hello {NUM}there{STRING} world").unwrap()
+        );
+
+        let source = "{RED}Hello {NUM}there{STRING}";
+        assert!(parsed.reparse_range(source, 29..29).is_ok());
+        assert_eq!(parsed, ParsedString::parse(source).unwrap());
+    }
+
     #[test]
     fn test_parse_str_err() {
         let case1 = ParsedString::parse("{G=n}{ORANGE OpenTTD");
@@ -643,6 +3275,8 @@ mod tests {
             Some(ParserError {
                 pos_begin: 5,
                 pos_end: None,
+                byte_begin: 5,
+                byte_end: None,
                 message: String::from("Unterminated string command, '}' expected."),
             })
         );
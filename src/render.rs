@@ -0,0 +1,108 @@
+use crate::validate::{Severity, ValidationError};
+
+/// Presentation of validation results, for example for a terminal or a web page.
+pub trait FindingRenderer {
+    /**
+     * Render a single validation result.
+     *
+     * @param finding The validation result.
+     * @param source The string the validation result belongs to.
+     *
+     * @returns The rendered validation result.
+     */
+    fn render(&self, finding: &ValidationError, source: &str) -> String;
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Render as a single line, with the position as character range.
+pub struct PlainRenderer;
+
+impl FindingRenderer for PlainRenderer {
+    fn render(&self, finding: &ValidationError, _source: &str) -> String {
+        let mut result = String::from(severity_name(finding.severity));
+        if let Some(pos_begin) = finding.pos_begin {
+            result.push_str(&format!(
+                " at {}..{}",
+                pos_begin,
+                finding.pos_end.unwrap_or(pos_begin + 1)
+            ));
+        }
+        result.push_str(&format!(": {}", finding.message));
+        if let Some(suggestion) = &finding.suggestion {
+            result.push_str(&format!(" {}", suggestion));
+        }
+        result
+    }
+}
+
+/// Render on multiple lines, marking the position with carets below the source.
+pub struct TerminalRenderer;
+
+impl FindingRenderer for TerminalRenderer {
+    fn render(&self, finding: &ValidationError, source: &str) -> String {
+        let mut result = format!("{}: {}\n", severity_name(finding.severity), finding.message);
+        if let Some(pos_begin) = finding.pos_begin {
+            let len = finding
+                .pos_end
+                .map_or(1, |pos_end| pos_end.saturating_sub(pos_begin).max(1));
+            result.push_str(&format!("  {}\n", source));
+            result.push_str(&format!("  {}{}\n", " ".repeat(pos_begin), "^".repeat(len)));
+        }
+        if let Some(suggestion) = &finding.suggestion {
+            result.push_str(&format!("  = {}\n", suggestion));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::FindingKind;
+
+    #[test]
+    fn test_render() {
+        let finding = ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::UnknownCommand,
+            pos_begin: Some(5),
+            pos_end: Some(10),
+            message: String::from("Unknown string command '{FOO}'."),
+            suggestion: Some(String::from("Remove this command.")),
+        };
+        assert_eq!(
+            TerminalRenderer.render(&finding, "{RED}{FOO} text"),
+            "error: Unknown string command '{FOO}'.\n  \
+             {RED}{FOO} text\n       \
+             ^^^^^\n  \
+             = Remove this command.\n"
+        );
+        assert_eq!(
+            PlainRenderer.render(&finding, "{RED}{FOO} text"),
+            "error at 5..10: Unknown string command '{FOO}'. Remove this command."
+        );
+
+        let finding = ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::Untranslated,
+            pos_begin: None,
+            pos_end: None,
+            message: String::from("Translation is identical to the base language."),
+            suggestion: None,
+        };
+        assert_eq!(
+            TerminalRenderer.render(&finding, "text"),
+            "warning: Translation is identical to the base language.\n"
+        );
+        assert_eq!(
+            PlainRenderer.render(&finding, "text"),
+            "warning: Translation is identical to the base language."
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::validate::{FindingKind, Severity, ValidationError};
+use serde_json::json;
+
+/// Rule id of a kind of finding, like "nile/unknown-command".
+fn rule_id(kind: FindingKind) -> String {
+    format!("nile/{}", kind.as_str())
+}
+
+/**
+ * Export validation results in the SARIF format, for code scanning tools.
+ *
+ * @param findings The validation results of a single string.
+ * @param source_name The name of the file or string the findings belong to.
+ *
+ * @returns The SARIF log as JSON.
+ */
+pub fn findings_to_sarif(findings: &[ValidationError], source_name: &str) -> String {
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|f| {
+            let mut message = f.message.clone();
+            if let Some(suggestion) = &f.suggestion {
+                message.push(' ');
+                message.push_str(suggestion);
+            }
+            let mut region = json!({ "startLine": 1 });
+            if let Some(pos_begin) = f.pos_begin {
+                region["startColumn"] = json!(pos_begin + 1);
+            }
+            if let Some(pos_end) = f.pos_end {
+                region["endColumn"] = json!(pos_end + 1);
+            }
+            json!({
+                "ruleId": rule_id(f.kind),
+                "level": match f.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": source_name },
+                        "region": region,
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    let rules: Vec<serde_json::Value> = FindingKind::ALL
+        .iter()
+        .map(|k| json!({ "id": rule_id(*k) }))
+        .collect();
+    let log = json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "nile-validator",
+                    "rules": rules,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    log.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_findings_to_sarif() {
+        let findings = vec![
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: Some(5),
+                pos_end: Some(10),
+                message: String::from("Unknown string command '{FOO}'."),
+                suggestion: None,
+            },
+            ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::Untranslated,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Translation is identical to the base language."),
+                suggestion: Some(String::from("Translate the text.")),
+            },
+        ];
+        let sarif = findings_to_sarif(&findings, "STR_HELLO");
+        let log: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "nile/unknown-command");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["message"]["text"],
+            "Unknown string command '{FOO}'."
+        );
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "STR_HELLO");
+        assert_eq!(location["region"]["startColumn"], 6);
+        assert_eq!(location["region"]["endColumn"], 11);
+
+        assert_eq!(results[1]["ruleId"], "nile/untranslated");
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(
+            results[1]["message"]["text"],
+            "Translation is identical to the base language. Translate the text."
+        );
+        let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert!(region.get("startColumn").is_none());
+
+        let rules = log["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rules.len(), FindingKind::ALL.len());
+        assert!(rules.iter().any(|r| r["id"] == "nile/unknown-command"));
+
+        let empty: serde_json::Value =
+            serde_json::from_str(&findings_to_sarif(&[], "STR_HELLO")).unwrap();
+        assert_eq!(empty["runs"][0]["results"].as_array().unwrap().len(), 0);
+    }
+}
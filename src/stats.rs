@@ -0,0 +1,146 @@
+use crate::parser::{FragmentContent, ParsedString};
+use crate::validate::{FindingKind, Severity, ValidationError};
+use std::collections::BTreeMap;
+
+/// Statistics collected over many strings, for example a whole language file.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub strings: usize,
+    pub parameters: usize,
+    pub commands: BTreeMap<String, usize>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub kinds: BTreeMap<FindingKind, usize>,
+}
+
+impl Stats {
+    /// Record the string commands of a string.
+    pub fn record(&mut self, parsed: &ParsedString) {
+        self.strings += 1;
+        self.parameters += parsed.parameters().len();
+        for fragment in &parsed.fragments {
+            if let FragmentContent::Command(cmd) = &fragment.content {
+                *self.commands.entry(cmd.name.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Record the validation results of a string.
+    pub fn record_errors(&mut self, errors: &[ValidationError]) {
+        for err in errors {
+            match err.severity {
+                Severity::Error => self.errors += 1,
+                Severity::Warning => self.warnings += 1,
+            }
+            *self.kinds.entry(err.kind).or_default() += 1;
+        }
+    }
+
+    pub fn average_parameters(&self) -> f64 {
+        if self.strings == 0 {
+            0.0
+        } else {
+            self.parameters as f64 / self.strings as f64
+        }
+    }
+
+    /// Summary of the statistics, one item per line.
+    /// Findings are listed by kind; commands are listed by frequency, most used first.
+    pub fn report(&self) -> String {
+        let mut result = format!(
+            "Strings: {}\nParameters per string: {:.2}\nErrors: {}\nWarnings: {}\n",
+            self.strings,
+            self.average_parameters(),
+            self.errors,
+            self.warnings
+        );
+        for (kind, count) in &self.kinds {
+            result.push_str(&format!("{}: {}\n", kind.as_str(), count));
+        }
+        let mut commands: Vec<(&String, &usize)> = self.commands.iter().collect();
+        commands.sort_by(|a, b| b.1.cmp(a.1));
+        for (name, count) in commands {
+            result.push_str(&format!("{{{}}}: {}\n", name, count));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.average_parameters(), 0.0);
+
+        stats.record(&ParsedString::parse("{RED}{NUM} {STRING}{RED}").unwrap());
+        stats.record(&ParsedString::parse("Text").unwrap());
+        stats.record(&ParsedString::parse("{RED}{COMMA}").unwrap());
+        stats.record_errors(&[
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("a"),
+                suggestion: None,
+            },
+            ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::Untranslated,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("b"),
+                suggestion: None,
+            },
+            ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::TabCharacter,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("c"),
+                suggestion: None,
+            },
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("d"),
+                suggestion: None,
+            },
+        ]);
+
+        assert_eq!(stats.strings, 3);
+        assert_eq!(stats.parameters, 3);
+        assert_eq!(stats.average_parameters(), 1.0);
+        assert_eq!(stats.commands.get("RED"), Some(&3));
+        assert_eq!(stats.commands.get("NUM"), Some(&1));
+        assert_eq!(stats.errors, 2);
+        assert_eq!(stats.warnings, 2);
+        assert_eq!(
+            stats.kinds,
+            BTreeMap::from([
+                (FindingKind::Untranslated, 1),
+                (FindingKind::UnknownCommand, 2),
+                (FindingKind::TabCharacter, 1),
+            ])
+        );
+        assert_eq!(
+            stats.report(),
+            "Strings: 3\n\
+             Parameters per string: 1.00\n\
+             Errors: 2\n\
+             Warnings: 2\n\
+             untranslated: 1\n\
+             unknown-command: 2\n\
+             tab-character: 1\n\
+             {RED}: 3\n\
+             {COMMA}: 1\n\
+             {NUM}: 1\n\
+             {STRING}: 1\n"
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use crate::commands::{CommandInfo, Occurence, COMMANDS};
-use crate::parser::{FragmentContent, ParsedString};
+use crate::commands::{
+    infer_param_kind, Category, CommandInfo, CommandMeta, Occurence, ParamKind, COMMANDS,
+};
+use crate::parser::{FragmentContent, ParsedString, StringCommand, StringFragment};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Dialect {
@@ -10,23 +14,90 @@ pub enum Dialect {
     OPENTTD,
 }
 
-#[derive(Deserialize, Debug)]
+/// Whether parameter positions in a string may have gaps.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum IndexPolicy {
+    Sparse, //< positions may be skipped, like '{0:NUM}{2:NUM}'.
+    Dense,  //< all positions up to the highest one must be used.
+}
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct LanguageConfig {
     pub dialect: Dialect,
     pub cases: Vec<String>,
     pub genders: Vec<String>,
     pub plural_count: usize,
+    #[serde(default)]
+    pub relaxed_genders: bool, //< accept any gender in definitions, for languages without a final list of genders.
+    #[serde(default)]
+    pub severities: SeverityMap, //< severity overrides for the findings of validate_base and validate_translation.
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Severity {
     Error,   //< translation is broken, do not commit.
     Warning, //< translation has minor issues, but is probably better than no translation.
 }
 
+/// Kind of a finding, which identifies the check that produced it.
+/// Unlike messages, kinds do not change when checks are reworded; tools can rely on them,
+/// for example to override severities via SeverityMap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FindingKind {
+    InvalidString,             //< String cannot be parsed.
+    InvalidBase,               //< Base string of a translation cannot be parsed.
+    EmptyTranslation,          //< Translation is empty.
+    Untranslated,              //< Translation is identical to the base.
+    UnknownCommand,            //< String command does not exist in the dialect.
+    UnknownCase,               //< Case is not a case of the language.
+    CaseNotAllowed,            //< Case is used where cases are not allowed.
+    CaseChange,                //< Case is added or removed, compared to the base.
+    PositionNotAllowed,        //< Position reference on a command without parameters.
+    SkippedPosition,           //< Positions of parameters have a gap.
+    UnknownPosition,           //< Position has no parameter in the base.
+    ParameterMismatch,         //< Parameter differs from the base.
+    ParameterKind,             //< Parameter looks like a different kind than in the base.
+    UnusedParameter,           //< Parameter of the base is not used.
+    IndexShift,                //< Position is off by one, compared to the base.
+    MissingCommand,            //< Command of the base is missing.
+    UnexpectedCommand,         //< Command is not in the base.
+    CommandCount,              //< Command is used a different number of times than in the base.
+    RepeatedCommand,           //< Command repeats the previous command.
+    MaxOccurrences,            //< Command is used more often than allowed.
+    MaxCommands,               //< String has too many commands.
+    EmptyCommand,              //< '{}' is used where it is not allowed.
+    RequiredPrefix,            //< String does not start with the required commands.
+    BoundaryCommands,          //< Commands at the start or end differ from the base.
+    GenderNotAllowed,          //< Genders are used where genders are not allowed.
+    UnknownGender,             //< Gender is not a gender of the language.
+    GenderDefinitionPosition,  //< Gender definition is not at the front.
+    DuplicateGenderDefinition, //< String has more than one gender definition.
+    DuplicateGenderChoice,     //< Gender choice is identical to the previous one.
+    PluralNotAllowed,          //< Plurals are used where plurals are not allowed.
+    ChoiceCount,               //< Choice list has the wrong number of items.
+    InvalidChoiceReference,    //< Choice list refers to an unsuitable parameter.
+    OrphanedChoice,            //< Choice list does not refer to any parameter.
+    SharedChoiceReference,     //< Plural and gender list refer to the same parameter.
+    BoundaryChoice,            //< Choice list at the start or end refers to a parameter implicitly.
+    LongChoiceItem,            //< Choice item is much longer than the other items.
+    EndColour,                 //< String ends with a colour, where the base does not.
+    ColourNotAllowed,          //< Colour is used where colours are not allowed.
+    ColourOrder,               //< Colours differ from the base.
+    LineBreaks,                //< Number of line breaks differs from the base.
+    MaxLength,                 //< String is longer than allowed.
+    TabCharacter,              //< Text contains a tab.
+    InvisibleCharacter,        //< Text contains an invisible character.
+    FormatSpecifier,           //< Text contains a printf-style format specifier.
+    UnknownStringReference,    //< Referenced string does not exist.
+    InvalidLine,               //< Line of a language file is not a string.
+    UnreadableFile,            //< Language file cannot be read.
+    UnknownString,             //< String is not in the base language.
+}
+
 #[derive(Serialize, Debug, PartialEq)]
 pub struct ValidationError {
     pub severity: Severity,
+    pub kind: FindingKind,
     pub pos_begin: Option<usize>, //< codepoint offset in input string
     pub pos_end: Option<usize>,
     pub message: String,
@@ -39,6 +110,101 @@ pub struct ValidationResult {
     pub normalized: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    All,      //< run all checks, and report all findings.
+    FailFast, //< stop at the first finding.
+}
+
+type Check = Box<dyn Fn(&ParsedString) -> Vec<ValidationError>>;
+
+/// A list of checks, to run on a string together.
+#[derive(Default)]
+pub struct Validator {
+    checks: Vec<Check>,
+    severities: SeverityMap,
+}
+
+/// Severity overrides for kinds of findings, to tune the strictness of checks.
+/// The default map has no overrides, so findings keep the severity of their check.
+/// In JSON, the map is an object like '{"unknown-command": "warning"}'.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct SeverityMap {
+    overrides: HashMap<FindingKind, Severity>,
+}
+
+/// A proposed change to a string, see suggest_fixes.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Fix {
+    pub pos_begin: usize, //< codepoint offset in input string
+    pub pos_end: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Builtin metadata of OpenTTD languages: isocode, plural count, genders and cases.
+const LANGUAGES: &[(&str, usize, &[&str], &[&str])] = &[
+    ("en_GB", 2, &[], &[]),
+    ("en_US", 2, &[], &[]),
+    ("nl_NL", 2, &[], &[]),
+    ("fr_FR", 2, &["m", "f"], &[]),
+    (
+        "pl_PL",
+        3,
+        &["m", "f", "n"],
+        &["d", "c", "b", "n", "m", "w"],
+    ),
+    (
+        "ru_RU",
+        3,
+        &["m", "f", "n", "p"],
+        &["m", "r", "d", "v", "t", "p"],
+    ),
+    ("ja_JP", 1, &[], &[]),
+];
+
+/// Number of plural forms of each OpenTTD plural form, indexed by the value of '##plural'.
+const PLURAL_COUNTS: &[usize] = &[2, 1, 2, 3, 5, 3, 3, 3, 4, 2, 3, 2, 4, 4, 3];
+
+impl LanguageConfig {
+    /// Whether gender definitions and gender choices are allowed.
+    /// Languages need at least two genders, unless the list of genders is not final yet.
+    fn allow_genders(&self) -> bool {
+        self.dialect.allow_genders() && (self.relaxed_genders || self.genders.len() >= 2)
+    }
+
+    /**
+     * Get the configuration of a known OpenTTD language.
+     *
+     * @param code The isocode of the language, for example "pl_PL".
+     *
+     * @returns The configuration for the OpenTTD dialect, or None if the language is unknown.
+     */
+    pub fn for_language(code: &str) -> Option<LanguageConfig> {
+        let (_, plural_count, genders, cases) = LANGUAGES.iter().find(|l| l.0 == code)?;
+        Some(LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: cases.iter().map(|c| String::from(*c)).collect(),
+            genders: genders.iter().map(|g| String::from(*g)).collect(),
+            plural_count: *plural_count,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        })
+    }
+
+    /**
+     * Get the number of plural forms of an OpenTTD plural form.
+     *
+     * @param form The plural form, as in the '##plural' header of a language file.
+     *
+     * @returns The number of plural forms, or None if the plural form is unknown.
+     */
+    pub fn plural_count_of_form(form: usize) -> Option<usize> {
+        PLURAL_COUNTS.get(form).copied()
+    }
+}
+
 impl Dialect {
     pub fn allow_cases(&self) -> bool {
         *self != Self::GAMESCRIPT
@@ -48,6 +214,18 @@ impl Dialect {
         *self != Self::GAMESCRIPT
     }
 
+    /// Whether gender definitions can take the gender from a parameter, like '{G=0}'.
+    pub fn allow_gender_refs(&self) -> bool {
+        *self == Self::OPENTTD
+    }
+
+    pub fn index_policy(&self) -> IndexPolicy {
+        match self {
+            Self::NEWGRF | Self::OPENTTD => IndexPolicy::Sparse,
+            Self::GAMESCRIPT => IndexPolicy::Dense,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::NEWGRF => "newgrf",
@@ -95,6 +273,23 @@ impl Serialize for Dialect {
     }
 }
 
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        match string.as_str() {
+            "error" => Ok(Self::Error),
+            "warning" => Ok(Self::Warning),
+            _ => Err(serde::de::Error::unknown_variant(
+                string.as_str(),
+                &["error", "warning"],
+            )),
+        }
+    }
+}
+
 impl Serialize for Severity {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -107,6 +302,145 @@ impl Serialize for Severity {
     }
 }
 
+impl FindingKind {
+    /// All kinds of findings.
+    pub const ALL: &'static [FindingKind] = &[
+        FindingKind::InvalidString,
+        FindingKind::InvalidBase,
+        FindingKind::EmptyTranslation,
+        FindingKind::Untranslated,
+        FindingKind::UnknownCommand,
+        FindingKind::UnknownCase,
+        FindingKind::CaseNotAllowed,
+        FindingKind::CaseChange,
+        FindingKind::PositionNotAllowed,
+        FindingKind::SkippedPosition,
+        FindingKind::UnknownPosition,
+        FindingKind::ParameterMismatch,
+        FindingKind::ParameterKind,
+        FindingKind::UnusedParameter,
+        FindingKind::IndexShift,
+        FindingKind::MissingCommand,
+        FindingKind::UnexpectedCommand,
+        FindingKind::CommandCount,
+        FindingKind::RepeatedCommand,
+        FindingKind::MaxOccurrences,
+        FindingKind::MaxCommands,
+        FindingKind::EmptyCommand,
+        FindingKind::RequiredPrefix,
+        FindingKind::BoundaryCommands,
+        FindingKind::GenderNotAllowed,
+        FindingKind::UnknownGender,
+        FindingKind::GenderDefinitionPosition,
+        FindingKind::DuplicateGenderDefinition,
+        FindingKind::DuplicateGenderChoice,
+        FindingKind::PluralNotAllowed,
+        FindingKind::ChoiceCount,
+        FindingKind::InvalidChoiceReference,
+        FindingKind::OrphanedChoice,
+        FindingKind::SharedChoiceReference,
+        FindingKind::BoundaryChoice,
+        FindingKind::LongChoiceItem,
+        FindingKind::EndColour,
+        FindingKind::ColourNotAllowed,
+        FindingKind::ColourOrder,
+        FindingKind::LineBreaks,
+        FindingKind::MaxLength,
+        FindingKind::TabCharacter,
+        FindingKind::InvisibleCharacter,
+        FindingKind::FormatSpecifier,
+        FindingKind::UnknownStringReference,
+        FindingKind::InvalidLine,
+        FindingKind::UnreadableFile,
+        FindingKind::UnknownString,
+    ];
+
+    /// Identifier of the kind, like "unknown-command".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidString => "invalid-string",
+            Self::InvalidBase => "invalid-base",
+            Self::EmptyTranslation => "empty-translation",
+            Self::Untranslated => "untranslated",
+            Self::UnknownCommand => "unknown-command",
+            Self::UnknownCase => "unknown-case",
+            Self::CaseNotAllowed => "case-not-allowed",
+            Self::CaseChange => "case-change",
+            Self::PositionNotAllowed => "position-not-allowed",
+            Self::SkippedPosition => "skipped-position",
+            Self::UnknownPosition => "unknown-position",
+            Self::ParameterMismatch => "parameter-mismatch",
+            Self::ParameterKind => "parameter-kind",
+            Self::UnusedParameter => "unused-parameter",
+            Self::IndexShift => "index-shift",
+            Self::MissingCommand => "missing-command",
+            Self::UnexpectedCommand => "unexpected-command",
+            Self::CommandCount => "command-count",
+            Self::RepeatedCommand => "repeated-command",
+            Self::MaxOccurrences => "max-occurrences",
+            Self::MaxCommands => "max-commands",
+            Self::EmptyCommand => "empty-command",
+            Self::RequiredPrefix => "required-prefix",
+            Self::BoundaryCommands => "boundary-commands",
+            Self::GenderNotAllowed => "gender-not-allowed",
+            Self::UnknownGender => "unknown-gender",
+            Self::GenderDefinitionPosition => "gender-definition-position",
+            Self::DuplicateGenderDefinition => "duplicate-gender-definition",
+            Self::DuplicateGenderChoice => "duplicate-gender-choice",
+            Self::PluralNotAllowed => "plural-not-allowed",
+            Self::ChoiceCount => "choice-count",
+            Self::InvalidChoiceReference => "invalid-choice-reference",
+            Self::OrphanedChoice => "orphaned-choice",
+            Self::SharedChoiceReference => "shared-choice-reference",
+            Self::BoundaryChoice => "boundary-choice",
+            Self::LongChoiceItem => "long-choice-item",
+            Self::EndColour => "end-colour",
+            Self::ColourNotAllowed => "colour-not-allowed",
+            Self::ColourOrder => "colour-order",
+            Self::LineBreaks => "line-breaks",
+            Self::MaxLength => "max-length",
+            Self::TabCharacter => "tab-character",
+            Self::InvisibleCharacter => "invisible-character",
+            Self::FormatSpecifier => "format-specifier",
+            Self::UnknownStringReference => "unknown-string-reference",
+            Self::InvalidLine => "invalid-line",
+            Self::UnreadableFile => "unreadable-file",
+            Self::UnknownString => "unknown-string",
+        }
+    }
+}
+
+impl TryFrom<&str> for FindingKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        FindingKind::ALL
+            .iter()
+            .find(|k| k.as_str() == value)
+            .copied()
+            .ok_or(format!("Unknown finding kind '{}'", value))
+    }
+}
+
+impl<'de> Deserialize<'de> for FindingKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        FindingKind::try_from(string.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for FindingKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 /**
  * Validate whether a base string is valid.
  *
@@ -115,12 +449,30 @@ impl Serialize for Severity {
  *
  * @returns A normalized form of the base string for translators, and a list of error messages, if the base is invalid.
  */
-pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult {
-    let mut base = match ParsedString::parse(&base) {
+pub fn validate_base(config: &LanguageConfig, base: &str) -> ValidationResult {
+    validate_base_with_commands(config, &COMMANDS, base)
+}
+
+/**
+ * Validate whether a base string is valid, against a custom source of command metadata.
+ *
+ * @param config The language configuration of the base language. (dialect and plural form)
+ * @param meta The command metadata, for example COMMANDS.
+ * @param base The base string to validate.
+ *
+ * @returns A normalized form of the base string for translators, and a list of error messages, if the base is invalid.
+ */
+pub fn validate_base_with_commands(
+    config: &LanguageConfig,
+    meta: &dyn CommandMeta,
+    base: &str,
+) -> ValidationResult {
+    let mut base = match ParsedString::parse(base) {
         Err(err) => {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::InvalidString,
                     pos_begin: Some(err.pos_begin),
                     pos_end: err.pos_end,
                     message: err.message,
@@ -131,15 +483,19 @@ pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult
         }
         Ok(parsed) => parsed,
     };
-    let errs = validate_string(&config, &base, None);
+    let mut errs = validate_string(config, meta, &base, None);
+    let skipped = get_noqa(&base);
+    errs.retain(|e| !skipped.contains(&e.kind));
+    config.severities.apply(&mut errs);
     if errs.iter().any(|e| e.severity == Severity::Error) {
         ValidationResult {
             errors: errs,
             normalized: None,
         }
     } else {
+        base.strip_comments();
         sanitize_whitespace(&mut base);
-        normalize_string(&config.dialect, &mut base);
+        normalize_string(meta, &config.dialect, &mut base);
         ValidationResult {
             errors: errs,
             normalized: Some(base.compile()),
@@ -159,15 +515,37 @@ pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult
  */
 pub fn validate_translation(
     config: &LanguageConfig,
-    base: &String,
-    case: &String,
-    translation: &String,
+    base: &str,
+    case: &str,
+    translation: &str,
+) -> ValidationResult {
+    validate_translation_with_commands(config, &COMMANDS, base, case, translation)
+}
+
+/**
+ * Validate whether a translation is valid for the given base string, against a custom source of command metadata.
+ *
+ * @param config The language configuration to validate against.
+ * @param meta The command metadata, for example COMMANDS.
+ * @param base The base string to validate against.
+ * @param case The case of the translation. Use "default" for the default case.
+ * @param translation The translation to validate.
+ *
+ * @returns A normalized form of the translation, and a list of error messages, if the translation is invalid.
+ */
+pub fn validate_translation_with_commands(
+    config: &LanguageConfig,
+    meta: &dyn CommandMeta,
+    base: &str,
+    case: &str,
+    translation: &str,
 ) -> ValidationResult {
-    let base = match ParsedString::parse(&base) {
+    let base = match ParsedString::parse(base) {
         Err(_) => {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::InvalidBase,
                     pos_begin: None,
                     pos_end: None,
                     message: String::from("Base language text is invalid."),
@@ -183,6 +561,7 @@ pub fn validate_translation(
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::CaseNotAllowed,
                     pos_begin: None,
                     pos_end: None,
                     message: String::from("No cases allowed."),
@@ -190,10 +569,11 @@ pub fn validate_translation(
                 }],
                 normalized: None,
             };
-        } else if !config.cases.contains(&case) {
+        } else if !config.cases.iter().any(|c| c == case) {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::UnknownCase,
                     pos_begin: None,
                     pos_end: None,
                     message: format!("Unknown case '{}'.", case),
@@ -203,11 +583,12 @@ pub fn validate_translation(
             };
         }
     }
-    let mut translation = match ParsedString::parse(&translation) {
+    let mut translation = match ParsedString::parse(translation) {
         Err(err) => {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::InvalidString,
                     pos_begin: Some(err.pos_begin),
                     pos_end: err.pos_end,
                     message: err.message,
@@ -218,15 +599,19 @@ pub fn validate_translation(
         }
         Ok(parsed) => parsed,
     };
-    let errs = validate_string(&config, &translation, Some(&base));
+    let mut errs = validate_string(config, meta, &translation, Some(&base));
+    let skipped = get_noqa(&translation);
+    errs.retain(|e| !skipped.contains(&e.kind));
+    config.severities.apply(&mut errs);
     if errs.iter().any(|e| e.severity == Severity::Error) {
         ValidationResult {
             errors: errs,
             normalized: None,
         }
     } else {
+        translation.strip_comments();
         sanitize_whitespace(&mut translation);
-        normalize_string(&config.dialect, &mut translation);
+        normalize_string(meta, &config.dialect, &mut translation);
         ValidationResult {
             errors: errs,
             normalized: Some(translation.compile()),
@@ -269,33 +654,42 @@ fn sanitize_whitespace(parsed: &mut ParsedString) {
     }
 }
 
-struct StringSignature {
-    parameters: HashMap<usize, (&'static CommandInfo<'static>, usize)>,
+/// Check whether a string has no content, besides whitespace and comments.
+fn is_blank(parsed: &ParsedString) -> bool {
+    parsed.fragments.iter().all(|f| match &f.content {
+        FragmentContent::Text(t) => t.trim().is_empty(),
+        FragmentContent::Comment(_) => true,
+        _ => false,
+    })
+}
+
+struct StringSignature<'m> {
+    parameters: HashMap<usize, (&'m CommandInfo<'m>, usize)>,
     nonpositional_count: BTreeMap<String, (Occurence, usize)>,
     // TODO track color/lineno/colorstack for positional parameters
 }
 
-fn get_signature(
+fn get_signature<'m>(
+    meta: &'m dyn CommandMeta,
     dialect: &Dialect,
     base: &ParsedString,
-) -> Result<StringSignature, Vec<ValidationError>> {
+) -> Result<StringSignature<'m>, Vec<ValidationError>> {
     let mut errors = Vec::new();
     let mut signature = StringSignature {
         parameters: HashMap::new(),
         nonpositional_count: BTreeMap::new(),
     };
 
+    let mut first_use = BTreeMap::new();
     let mut pos = 0;
     for fragment in &base.fragments {
         if let FragmentContent::Command(cmd) = &fragment.content {
-            if let Some(info) = COMMANDS
-                .into_iter()
-                .find(|ci| ci.name == cmd.name && ci.dialects.contains(&dialect))
-            {
-                if info.parameters.is_empty() {
+            if let Some(info) = meta.get(dialect, &cmd.name) {
+                if info.arity() == 0 {
                     if let Some(index) = cmd.index {
                         errors.push(ValidationError {
                             severity: Severity::Error,
+                            kind: FindingKind::PositionNotAllowed,
                             pos_begin: Some(fragment.pos_begin),
                             pos_end: Some(fragment.pos_end),
                             message: format!(
@@ -311,7 +705,7 @@ fn get_signature(
                     } else {
                         signature
                             .nonpositional_count
-                            .insert(norm_name, (info.occurence.clone(), 1));
+                            .insert(norm_name, (info.occurence, 1));
                     }
                 } else {
                     if let Some(index) = cmd.index {
@@ -321,12 +715,14 @@ fn get_signature(
                         existing.1 += 1;
                     } else {
                         signature.parameters.insert(pos, (info, 1));
+                        first_use.insert(pos, fragment);
                     }
                     pos += 1;
                 }
             } else {
                 errors.push(ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::UnknownCommand,
                     pos_begin: Some(fragment.pos_begin),
                     pos_end: Some(fragment.pos_end),
                     message: format!("Unknown string command '{{{}}}'.", cmd.name),
@@ -336,6 +732,26 @@ fn get_signature(
         }
     }
 
+    if dialect.index_policy() == IndexPolicy::Dense {
+        let mut expected = 0;
+        for (index, fragment) in &first_use {
+            if *index > expected {
+                errors.push(ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::SkippedPosition,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: format!(
+                        "Position {} is used, but position {} is not.",
+                        index, expected
+                    ),
+                    suggestion: Some(String::from("Do not skip positions.")),
+                });
+            }
+            expected = index + 1;
+        }
+    }
+
     if errors.is_empty() {
         Ok(signature)
     } else {
@@ -345,16 +761,18 @@ fn get_signature(
 
 fn validate_string(
     config: &LanguageConfig,
+    meta: &dyn CommandMeta,
     test: &ParsedString,
     base: Option<&ParsedString>,
 ) -> Vec<ValidationError> {
     let signature: StringSignature;
-    match get_signature(&config.dialect, base.unwrap_or(test)) {
+    match get_signature(meta, &config.dialect, base.unwrap_or(test)) {
         Ok(sig) => signature = sig,
         Err(msgs) => {
             if base.is_some() {
                 return vec![ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::InvalidBase,
                     pos_begin: None,
                     pos_end: None,
                     message: String::from("Base language text is invalid."),
@@ -367,6 +785,17 @@ fn validate_string(
     }
 
     let mut errors = Vec::new();
+    if base.is_some_and(|b| !is_blank(b)) && is_blank(test) {
+        errors.push(ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::EmptyTranslation,
+            pos_begin: None,
+            pos_end: None,
+            message: String::from("Translation is empty."),
+            suggestion: None,
+        });
+    }
+
     let mut positional_count: HashMap<usize, usize> = HashMap::new();
     let mut nonpositional_count: BTreeMap<String, (Occurence, usize)> = BTreeMap::new();
     let mut pos = 0;
@@ -377,18 +806,16 @@ fn validate_string(
                 let opt_expected = signature
                     .parameters
                     .get(&cmd.index.unwrap_or(pos))
-                    .map(|v| (*v).0);
-                let opt_info =
-                    opt_expected
-                        .filter(|ex| ex.get_norm_name() == cmd.name)
-                        .or(COMMANDS.into_iter().find(|ci| {
-                            ci.name == cmd.name && ci.dialects.contains(&config.dialect)
-                        }));
+                    .map(|v| v.0);
+                let opt_info = opt_expected
+                    .filter(|ex| ex.get_norm_name() == cmd.name)
+                    .or(meta.get(&config.dialect, &cmd.name));
                 if let Some(info) = opt_info {
                     if let Some(c) = &cmd.case {
                         if !config.dialect.allow_cases() {
                             errors.push(ValidationError {
                                 severity: Severity::Error,
+                                kind: FindingKind::CaseNotAllowed,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: String::from("No case selections allowed."),
@@ -397,6 +824,7 @@ fn validate_string(
                         } else if !info.allow_case {
                             errors.push(ValidationError {
                                 severity: Severity::Error,
+                                kind: FindingKind::CaseNotAllowed,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: format!(
@@ -405,9 +833,10 @@ fn validate_string(
                                 ),
                                 suggestion: Some(format!("Remove '.{}'.", c)),
                             });
-                        } else if !config.cases.contains(&c) {
+                        } else if !config.cases.contains(c) {
                             errors.push(ValidationError {
                                 severity: Severity::Error,
+                                kind: FindingKind::UnknownCase,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: format!("Unknown case '{}'.", c),
@@ -419,10 +848,11 @@ fn validate_string(
                         }
                     }
 
-                    if info.parameters.is_empty() {
+                    if info.arity() == 0 {
                         if let Some(index) = cmd.index {
                             errors.push(ValidationError {
                                 severity: Severity::Error,
+                                kind: FindingKind::PositionNotAllowed,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: format!(
@@ -437,7 +867,7 @@ fn validate_string(
                         if let Some(existing) = nonpositional_count.get_mut(&norm_name) {
                             existing.1 += 1;
                         } else {
-                            nonpositional_count.insert(norm_name, (info.occurence.clone(), 1));
+                            nonpositional_count.insert(norm_name, (info.occurence, 1));
                         }
                     } else {
                         if let Some(index) = cmd.index {
@@ -452,20 +882,36 @@ fn validate_string(
                                     positional_count.insert(pos, 1);
                                 }
                             } else {
+                                // suggest position reference, if parameters are reordered
+                                let mut others = signature
+                                    .parameters
+                                    .iter()
+                                    .filter(|(_, (ex, _))| {
+                                        ex.get_norm_name() == info.get_norm_name()
+                                    })
+                                    .map(|(p, _)| *p);
                                 errors.push(ValidationError {
                                     severity: Severity::Error,
+                                    kind: FindingKind::ParameterMismatch,
                                     pos_begin: Some(fragment.pos_begin),
                                     pos_end: Some(fragment.pos_end),
                                     message: format!(
                                         "Expected '{{{}:{}}}', found '{{{}}}'.",
                                         pos, expected.name, cmd.name
                                     ),
-                                    suggestion: None,
+                                    suggestion: match (cmd.index, others.next(), others.next()) {
+                                        (None, Some(other), None) => Some(format!(
+                                            "Use '{{{}:{}}}' to reorder parameters.",
+                                            other, cmd.name
+                                        )),
+                                        _ => None,
+                                    },
                                 })
                             }
                         } else {
                             errors.push(ValidationError {
                                 severity: Severity::Error,
+                                kind: FindingKind::UnknownPosition,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: format!(
@@ -481,6 +927,7 @@ fn validate_string(
                 } else {
                     errors.push(ValidationError {
                         severity: Severity::Error,
+                        kind: FindingKind::UnknownCommand,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: format!("Unknown string command '{{{}}}'.", cmd.name),
@@ -490,9 +937,10 @@ fn validate_string(
                 front = 2;
             }
             FragmentContent::Gender(g) => {
-                if !config.dialect.allow_genders() || config.genders.len() < 2 {
+                if !config.allow_genders() {
                     errors.push(ValidationError {
                         severity: Severity::Error,
+                        kind: FindingKind::GenderNotAllowed,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: String::from("No gender definitions allowed."),
@@ -501,6 +949,7 @@ fn validate_string(
                 } else if front == 2 {
                     errors.push(ValidationError {
                         severity: Severity::Warning,
+                        kind: FindingKind::GenderDefinitionPosition,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: String::from("Gender definitions must be at the front."),
@@ -511,6 +960,7 @@ fn validate_string(
                 } else if front == 1 {
                     errors.push(ValidationError {
                         severity: Severity::Warning,
+                        kind: FindingKind::DuplicateGenderDefinition,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: String::from("Duplicate gender definition."),
@@ -518,12 +968,42 @@ fn validate_string(
                     });
                 } else {
                     front = 1;
-                    if !config.genders.contains(&g.gender) {
+                    if let Some(i) = g.indexref {
+                        if !config.dialect.allow_gender_refs() {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                kind: FindingKind::PositionNotAllowed,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: String::from(
+                                    "Gender definitions cannot reference a parameter.",
+                                ),
+                                suggestion: Some(String::from("Use the name of a gender.")),
+                            });
+                        } else if !signature.parameters.contains_key(&i) {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                kind: FindingKind::InvalidChoiceReference,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "Gender definition references position {}, which has no parameter.",
+                                    i
+                                ),
+                                suggestion: None,
+                            });
+                        }
+                    } else if let Some(gender) = g
+                        .gender
+                        .as_ref()
+                        .filter(|name| !config.relaxed_genders && !config.genders.contains(name))
+                    {
                         errors.push(ValidationError {
                             severity: Severity::Error,
+                            kind: FindingKind::UnknownGender,
                             pos_begin: Some(fragment.pos_begin),
                             pos_end: Some(fragment.pos_end),
-                            message: format!("Unknown gender '{}'.", g.gender),
+                            message: format!("Unknown gender '{}'.", gender),
                             suggestion: Some(format!(
                                 "Known genders are: '{}'",
                                 config.genders.join("', '")
@@ -545,10 +1025,10 @@ fn validate_string(
                     _ => panic!(),
                 };
                 let opt_ref_pos = cmd.indexref.or(opt_ref_pos);
-                if cmd.name == "G" && (!config.dialect.allow_genders() || config.genders.len() < 2)
-                {
+                if cmd.name == "G" && !config.allow_genders() {
                     errors.push(ValidationError {
                         severity: Severity::Error,
+                        kind: FindingKind::GenderNotAllowed,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: String::from("No gender choices allowed."),
@@ -557,6 +1037,7 @@ fn validate_string(
                 } else if cmd.name == "P" && config.plural_count < 2 {
                     errors.push(ValidationError {
                         severity: Severity::Error,
+                        kind: FindingKind::PluralNotAllowed,
                         pos_begin: Some(fragment.pos_begin),
                         pos_end: Some(fragment.pos_end),
                         message: String::from("No plural choices allowed."),
@@ -568,6 +1049,7 @@ fn validate_string(
                             if cmd.choices.len() != config.plural_count {
                                 errors.push(ValidationError {
                                     severity: Severity::Error,
+                                    kind: FindingKind::ChoiceCount,
                                     pos_begin: Some(fragment.pos_begin),
                                     pos_end: Some(fragment.pos_end),
                                     message: format!(
@@ -580,9 +1062,12 @@ fn validate_string(
                             }
                         }
                         "G" => {
-                            if cmd.choices.len() != config.genders.len() {
+                            // without a list of genders, relaxed mode has nothing to count against
+                            let unknown = config.relaxed_genders && config.genders.is_empty();
+                            if !unknown && cmd.choices.len() != config.genders.len() {
                                 errors.push(ValidationError {
                                     severity: Severity::Error,
+                                    kind: FindingKind::ChoiceCount,
                                     pos_begin: Some(fragment.pos_begin),
                                     pos_end: Some(fragment.pos_end),
                                     message: format!(
@@ -616,6 +1101,7 @@ fn validate_string(
                                     if !par_info.allow_plural {
                                         errors.push(ValidationError{
                                             severity: Severity::Error,
+                                            kind: FindingKind::InvalidChoiceReference,
                                             pos_begin: Some(fragment.pos_begin),
                                             pos_end: Some(fragment.pos_end),
                                             message: format!(
@@ -630,6 +1116,7 @@ fn validate_string(
                                     if !par_info.allow_gender {
                                         errors.push(ValidationError{
                                             severity: Severity::Error,
+                                            kind: FindingKind::InvalidChoiceReference,
                                             pos_begin: Some(fragment.pos_begin),
                                             pos_end: Some(fragment.pos_end),
                                             message: format!(
@@ -645,18 +1132,32 @@ fn validate_string(
                         } else {
                             errors.push(ValidationError{
                                 severity: Severity::Error,
+                                kind: FindingKind::InvalidChoiceReference,
                                 pos_begin: Some(fragment.pos_begin),
                                 pos_end: Some(fragment.pos_end),
                                 message: format!(
                                     "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' only has {} subindices.",
-                                    cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name, ref_info.parameters.len()
+                                    cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name, ref_info.arity()
                                 ),
                                 suggestion: None,
                             });
                         }
+                    } else if signature.parameters.is_empty() {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            kind: FindingKind::InvalidChoiceReference,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "'{{{}}}' references a parameter, but the string has no parameters.",
+                                cmd.name
+                            ),
+                            suggestion: Some(format!("Remove '{{{} ...}}'.", cmd.name)),
+                        });
                     } else {
                         errors.push(ValidationError {
                             severity: Severity::Error,
+                            kind: FindingKind::InvalidChoiceReference,
                             pos_begin: Some(fragment.pos_begin),
                             pos_end: Some(fragment.pos_end),
                             message: format!(
@@ -676,9 +1177,12 @@ fn validate_string(
                 }
                 front = 2;
             }
-            FragmentContent::Text(_) => {
+            FragmentContent::Text(_)
+            | FragmentContent::Verbatim(_)
+            | FragmentContent::StringRef { .. } => {
                 front = 2;
             }
+            FragmentContent::Comment(_) => (),
         }
     }
 
@@ -688,6 +1192,7 @@ fn validate_string(
         if info.occurence != Occurence::ANY && found_count == 0 {
             errors.push(ValidationError {
                 severity: Severity::Error,
+                kind: FindingKind::MissingCommand,
                 pos_begin: None,
                 pos_end: None,
                 message: format!("String command '{{{}:{}}}' is missing.", pos, norm_name),
@@ -696,6 +1201,7 @@ fn validate_string(
         } else if info.occurence == Occurence::EXACT && *ex_count != found_count {
             errors.push(ValidationError {
                 severity: Severity::Warning,
+                kind: FindingKind::CommandCount,
                 pos_begin: None,
                 pos_end: None,
                 message: format!(
@@ -712,6 +1218,7 @@ fn validate_string(
         if *occurence != Occurence::ANY && found_count == 0 {
             errors.push(ValidationError {
                 severity: Severity::Warning,
+                kind: FindingKind::MissingCommand,
                 pos_begin: None,
                 pos_end: None,
                 message: format!("String command '{{{}}}' is missing.", norm_name),
@@ -720,6 +1227,7 @@ fn validate_string(
         } else if *occurence == Occurence::EXACT && *ex_count != found_count {
             errors.push(ValidationError {
                 severity: Severity::Warning,
+                kind: FindingKind::CommandCount,
                 pos_begin: None,
                 pos_end: None,
                 message: format!(
@@ -731,9 +1239,10 @@ fn validate_string(
         }
     }
     for (norm_name, (occurence, _)) in &nonpositional_count {
-        if *occurence != Occurence::ANY && signature.nonpositional_count.get(norm_name).is_none() {
+        if *occurence != Occurence::ANY && !signature.nonpositional_count.contains_key(norm_name) {
             errors.push(ValidationError {
                 severity: Severity::Warning,
+                kind: FindingKind::UnexpectedCommand,
                 pos_begin: None,
                 pos_end: None,
                 message: format!("String command '{{{}}}' is unexpected.", norm_name),
@@ -742,843 +1251,4225 @@ fn validate_string(
         }
     }
 
+    errors.extend(validate_repeated_style(meta, &config.dialect, test));
+    if let Some(base) = base {
+        errors.extend(validate_end_colour(meta, &config.dialect, test, base));
+    }
+
     errors
 }
 
-fn normalize_string(dialect: &Dialect, parsed: &mut ParsedString) {
-    let mut parameters = HashMap::new();
+impl Validator {
+    pub fn new() -> Validator {
+        Validator::default()
+    }
 
-    let mut pos = 0;
-    for fragment in &mut parsed.fragments {
-        match &mut fragment.content {
-            FragmentContent::Command(cmd) => {
-                if let Some(info) = COMMANDS
-                    .into_iter()
-                    .find(|ci| ci.name == cmd.name && ci.dialects.contains(&dialect))
-                {
-                    if let Some(norm_name) = info.norm_name {
-                        // normalize name
-                        cmd.name = String::from(norm_name);
-                    }
-                    if !info.parameters.is_empty() {
-                        if let Some(index) = cmd.index {
-                            pos = index;
-                        } else {
-                            // add missing indices
-                            cmd.index = Some(pos);
-                        }
-                        parameters.insert(pos, info);
-                        pos += 1;
-                    }
-                }
+    /// Add a check, which is run after all previously added checks.
+    pub fn with_check<F>(mut self, check: F) -> Validator
+    where
+        F: Fn(&ParsedString) -> Vec<ValidationError> + 'static,
+    {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// Use a severity map for the findings of all checks.
+    pub fn with_severities(mut self, severities: SeverityMap) -> Validator {
+        self.severities = severities;
+        self
+    }
+
+    /**
+     * Run the checks on a string.
+     *
+     * Findings of the kinds listed in a '{#noqa:kind}' comment of the string are skipped.
+     *
+     * @param parsed The string to validate.
+     * @param mode Whether to run all checks, or to stop at the first finding.
+     *
+     * @returns A list of error messages. With RunMode::FailFast, this contains at most one error.
+     */
+    pub fn run(&self, parsed: &ParsedString, mode: RunMode) -> Vec<ValidationError> {
+        let skipped = get_noqa(parsed);
+        let mut errors = Vec::new();
+        for check in &self.checks {
+            let mut findings = check(parsed);
+            findings.retain(|f| !skipped.contains(&f.kind));
+            self.severities.apply(&mut findings);
+            errors.extend(findings);
+            if mode == RunMode::FailFast && !errors.is_empty() {
+                errors.truncate(1);
+                break;
             }
-            FragmentContent::Choice(cmd) => {
-                match cmd.name.as_str() {
-                    "P" => {
-                        if cmd.indexref.is_none() && pos > 0 {
-                            // add missing indices
-                            cmd.indexref = Some(pos - 1);
-                        }
+        }
+        errors
+    }
+}
+
+/// Get the kinds of findings to skip for a string, from comments like '{#noqa:unknown-command,tab-character}'.
+/// Unknown kinds are ignored.
+fn get_noqa(parsed: &ParsedString) -> HashSet<FindingKind> {
+    parsed
+        .fragments
+        .iter()
+        .filter_map(|f| match &f.content {
+            FragmentContent::Comment(text) => text.trim().strip_prefix("noqa:"),
+            _ => None,
+        })
+        .flat_map(|kinds| kinds.split(',').map(str::trim))
+        .filter_map(|kind| FindingKind::try_from(kind).ok())
+        .collect()
+}
+
+impl SeverityMap {
+    pub fn new() -> SeverityMap {
+        SeverityMap::default()
+    }
+
+    /// Report findings of `kind` with `severity`.
+    pub fn with(mut self, kind: FindingKind, severity: Severity) -> SeverityMap {
+        self.overrides.insert(kind, severity);
+        self
+    }
+
+    /// Severity of a finding, after applying the overrides.
+    pub fn severity(&self, error: &ValidationError) -> Severity {
+        self.overrides
+            .get(&error.kind)
+            .copied()
+            .unwrap_or(error.severity)
+    }
+
+    /// Apply the overrides to a list of findings.
+    pub fn apply(&self, errors: &mut [ValidationError]) {
+        for error in errors {
+            error.severity = self.severity(error);
+        }
+    }
+}
+
+/**
+ * Merge the findings of several validations, removing duplicates.
+ *
+ * Findings of the same kind with overlapping spans report the same problem, even if the messages
+ * differ; only the one with the narrowest span is kept. Of findings without a span, exact
+ * duplicates are removed.
+ *
+ * @param findings The findings of all validations.
+ *
+ * @returns The findings without duplicates, in the order they were first found.
+ */
+pub fn dedup_findings(findings: Vec<ValidationError>) -> Vec<ValidationError> {
+    let span = |f: &ValidationError| f.pos_begin.zip(f.pos_end);
+    let mut result: Vec<ValidationError> = Vec::new();
+    for finding in findings {
+        let same = result.iter().position(|r| {
+            r.kind == finding.kind
+                && match (span(r), span(&finding)) {
+                    (Some((b1, e1)), Some((b2, e2))) => {
+                        (b1 < e2 && b2 < e1) || (b1, e1) == (b2, e2)
                     }
-                    "G" => {
-                        if cmd.indexref.is_none() {
-                            // add missing indices
-                            cmd.indexref = Some(pos);
-                        }
+                    (s1, s2) => {
+                        s1 == s2
+                            && r.message == finding.message
+                            && r.suggestion == finding.suggestion
                     }
-                    _ => panic!(),
+                }
+        });
+        match same {
+            Some(i) => {
+                let narrower = match (span(&result[i]), span(&finding)) {
+                    (Some((b1, e1)), Some((b2, e2))) => e2 - b2 < e1 - b1,
+                    _ => false,
                 };
+                if narrower {
+                    result[i] = finding;
+                }
             }
-            _ => (),
+            None => result.push(finding),
         }
     }
+    result
+}
 
-    for fragment in &mut parsed.fragments {
-        if let FragmentContent::Choice(cmd) = &mut fragment.content {
-            if let Some(ref_info) = cmd.indexref.and_then(|pos| parameters.get(&pos)) {
-                if cmd.indexsubref == ref_info.def_plural_subindex.or(Some(0)) {
-                    // remove subindex, if default
-                    cmd.indexsubref = None;
-                }
+/**
+ * Quickly check whether a translation uses the same parameters as the base.
+ *
+ * This does not parse the strings, but only scans for string commands. It is meant as a
+ * pre-filter before full validation. Exotic input, like braces inside choice lists, can
+ * lead to false negatives; so a failed check does not mean the translation is invalid.
+ *
+ * @param base The base string.
+ * @param translation The translation.
+ *
+ * @returns Whether the parameter-consuming commands match by position and name.
+ */
+pub fn quick_param_check(base: &str, translation: &str) -> bool {
+    fn scan(string: &str) -> Vec<(usize, &str)> {
+        let mut result = Vec::new();
+        let mut pos = 0;
+        let mut rest = string;
+        while let Some(start) = rest.find('{') {
+            rest = &rest[start + 1..];
+            let end = rest.find('}').unwrap_or(rest.len());
+            let mut token = &rest[..end];
+            rest = &rest[end..];
+
+            let mut index = None;
+            if let Some((prefix, name)) = token.split_once(':') {
+                index = prefix.parse().ok();
+                token = name;
+            }
+            let name = token.split('.').next().unwrap_or(token);
+            if let Some(info) = COMMANDS.iter().find(|ci| ci.name == name && ci.arity() > 0) {
+                pos = index.unwrap_or(pos);
+                result.push((pos, info.get_norm_name()));
+                pos += 1;
             }
         }
+        result.sort();
+        result
     }
+    scan(base) == scan(translation)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/**
+ * Propose fixes for a translation, without changing it.
+ *
+ * Fixes are proposed for:
+ *  - trailing whitespace, which differs from the base.
+ *  - case selections, which only differ in capitalization from a known case.
+ *  - colour and font commands, which repeat the previous command.
+ *
+ * @param test The translation to fix.
+ * @param base The base string.
+ * @param config The language configuration.
+ *
+ * @returns A list of fixes, ordered by position.
+ */
+pub fn suggest_fixes(
+    test: &ParsedString,
+    base: &ParsedString,
+    config: &LanguageConfig,
+) -> Vec<Fix> {
+    let mut fixes = Vec::new();
 
-    #[test]
-    fn test_sanitize() {
-        let mut s1 = String::from("");
-        let mut s2 = String::from(" a b c ");
-        let mut s3 = String::from("\0a\tb\rc\r\n");
-        let mut s4 = String::from("abc\u{b3}");
-        remove_ascii_ctrl(&mut s1);
-        remove_ascii_ctrl(&mut s2);
-        remove_ascii_ctrl(&mut s3);
-        remove_ascii_ctrl(&mut s4);
-        assert_eq!(s1, String::from(""));
-        assert_eq!(s2, String::from(" a b c "));
-        assert_eq!(s3, String::from(" a b c  "));
-        assert_eq!(s4, String::from("abc\u{b3}"));
-        remove_trailing_blanks(&mut s1);
-        remove_trailing_blanks(&mut s2);
-        remove_trailing_blanks(&mut s3);
-        remove_trailing_blanks(&mut s4);
-        assert_eq!(s1, String::from(""));
-        assert_eq!(s2, String::from(" a b c"));
-        assert_eq!(s3, String::from(" a b c"));
-        assert_eq!(s4, String::from("abc\u{b3}"));
+    for fragment in &test.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            let known = cmd.case.as_ref().and_then(|c| {
+                config
+                    .cases
+                    .iter()
+                    .find(|known| *known != c && known.eq_ignore_ascii_case(c))
+            });
+            if let Some(known) = known {
+                let old = cmd.compile();
+                let new = StringCommand {
+                    case: Some(known.clone()),
+                    ..cmd.clone()
+                }
+                .compile();
+                fixes.push(Fix {
+                    pos_begin: fragment.pos_begin,
+                    pos_end: fragment.pos_end,
+                    old,
+                    new,
+                });
+            }
+        }
     }
 
-    #[test]
-    fn test_signature_empty() {
-        let parsed = ParsedString::parse("").unwrap();
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert!(sig.parameters.is_empty());
-        assert!(sig.nonpositional_count.is_empty());
+    for err in validate_repeated_style(&COMMANDS, &config.dialect, test) {
+        if let (Some(pos_begin), Some(pos_end)) = (err.pos_begin, err.pos_end) {
+            let fragment = test.fragments.iter().find(|f| f.pos_begin == pos_begin);
+            fixes.push(Fix {
+                pos_begin,
+                pos_end,
+                old: fragment.map_or(String::new(), |f| f.content.compile()),
+                new: String::new(),
+            });
+        }
     }
 
-    #[test]
-    fn test_signature_pos() {
-        let parsed = ParsedString::parse("{P a b}{RED}{NUM}{NBSP}{MONO_FONT}{5:STRING.foo}{RED}{2:STRING3.bar}{RAW_STRING}{3:RAW_STRING}{G c d}").unwrap();
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert_eq!(sig.parameters.len(), 4);
-        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "NUM");
-        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&5).unwrap().0.name, "STRING");
-        assert_eq!(sig.parameters.get(&5).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&2).unwrap().0.name, "STRING3");
-        assert_eq!(sig.parameters.get(&2).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&3).unwrap().0.name, "RAW_STRING");
-        assert_eq!(sig.parameters.get(&3).unwrap().1, 2);
-        assert_eq!(sig.nonpositional_count.len(), 3);
-        assert_eq!(
-            sig.nonpositional_count.get("RED"),
-            Some(&(Occurence::NONZERO, 2))
-        );
-        assert_eq!(
-            sig.nonpositional_count.get("MONO_FONT"),
-            Some(&(Occurence::EXACT, 1))
-        );
-        assert_eq!(
-            sig.nonpositional_count.get("NBSP"),
-            Some(&(Occurence::ANY, 1))
-        );
+    let whitespace = test.trailing_whitespace();
+    if whitespace != base.trailing_whitespace() {
+        let pos_end = test.fragments.last().map_or(0, |f| f.pos_end);
+        fixes.push(Fix {
+            pos_begin: pos_end - whitespace.chars().count(),
+            pos_end,
+            old: String::from(whitespace),
+            new: String::from(base.trailing_whitespace()),
+        });
     }
 
-    #[test]
-    fn test_signature_dialect() {
-        let parsed = ParsedString::parse("{RAW_STRING}").unwrap();
-
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert_eq!(sig.parameters.len(), 1);
-        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "RAW_STRING");
-        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
-        assert_eq!(sig.nonpositional_count.len(), 0);
+    fixes.sort_by_key(|f| f.pos_begin);
+    fixes
+}
 
-        let err = get_signature(&Dialect::NEWGRF, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
-        assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(12),
-                message: String::from("Unknown string command '{RAW_STRING}'."),
-                suggestion: None,
-            }
-        );
+/**
+ * Validate a string for multiple dialects at once.
+ *
+ * @param config The language configuration. The dialect is replaced by each of the dialects.
+ * @param test The string to validate.
+ * @param base The base string to validate against, if the string is a translation.
+ * @param dialects The dialects to validate for.
+ *
+ * @returns A list of error messages, each with the dialect it applies to.
+ */
+pub fn validate_dialects(
+    config: &LanguageConfig,
+    test: &ParsedString,
+    base: Option<&ParsedString>,
+    dialects: &[Dialect],
+) -> Vec<(Dialect, ValidationError)> {
+    let mut result = Vec::new();
+    for dialect in dialects {
+        let config = LanguageConfig {
+            dialect: *dialect,
+            ..config.clone()
+        };
+        for err in validate_string(&config, &COMMANDS, test, base) {
+            result.push((*dialect, err));
+        }
     }
+    result
+}
 
-    #[test]
-    fn test_signature_unknown() {
-        let parsed = ParsedString::parse("{FOOBAR}").unwrap();
-        let err = get_signature(&Dialect::OPENTTD, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
-        assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(8),
-                message: String::from("Unknown string command '{FOOBAR}'."),
-                suggestion: None,
+/// Find colour and font commands, which repeat the directly preceding command.
+fn validate_repeated_style(
+    meta: &dyn CommandMeta,
+    dialect: &Dialect,
+    test: &ParsedString,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut previous: Option<&CommandInfo> = None;
+    for fragment in &test.fragments {
+        let info = match &fragment.content {
+            FragmentContent::Command(cmd) => meta.get(dialect, &cmd.name).filter(|ci| {
+                matches!(ci.category, Category::COLOUR | Category::FONT)
+                    && ci.arity() == 0
+                    && !matches!(ci.name, "PUSH_COLOUR" | "POP_COLOUR")
+            }),
+            _ => None,
+        };
+        if let Some(info) = info {
+            if previous.is_some_and(|p| p.get_norm_name() == info.get_norm_name()) {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::RepeatedCommand,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: format!(
+                        "String command '{{{}}}' repeats the previous command.",
+                        info.get_norm_name()
+                    ),
+                    suggestion: Some(String::from("Remove this command.")),
+                });
             }
-        );
+        }
+        previous = info;
     }
+    errors
+}
 
-    #[test]
-    fn test_signature_nonpos() {
-        let parsed = ParsedString::parse("{1:RED}").unwrap();
-        let err = get_signature(&Dialect::OPENTTD, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
-        assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(7),
-                message: String::from("Command '{RED}' cannot have a position reference."),
-                suggestion: Some(String::from("Remove '1:'.")),
+/// Get the colour command, which is active at the end of the string.
+/// Colours are tracked through '{PUSH_COLOUR}' and '{POP_COLOUR}'.
+/// The default colour '{BLACK}' is the same as no colour, and is returned as None.
+fn get_end_colour<'a>(
+    meta: &dyn CommandMeta,
+    dialect: &Dialect,
+    parsed: &'a ParsedString,
+) -> Option<(&'a str, &'a StringFragment)> {
+    let mut colour = None;
+    let mut stack = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if meta
+                .get(dialect, &cmd.name)
+                .is_some_and(|ci| ci.category == Category::COLOUR)
+            {
+                match cmd.name.as_str() {
+                    "PUSH_COLOUR" => stack.push(colour),
+                    "POP_COLOUR" => colour = stack.pop().flatten(),
+                    "BLACK" => colour = None,
+                    _ => colour = Some((cmd.name.as_str(), fragment)),
+                }
             }
-        );
+        }
     }
+    colour
+}
 
-    #[test]
-    fn test_validate_empty() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 0,
-        };
-        let base = ParsedString::parse("").unwrap();
-
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+fn validate_end_colour(
+    meta: &dyn CommandMeta,
+    dialect: &Dialect,
+    test: &ParsedString,
+    base: &ParsedString,
+) -> Vec<ValidationError> {
+    if get_end_colour(meta, dialect, base).is_some() {
+        return Vec::new();
+    }
+    match get_end_colour(meta, dialect, test) {
+        Some((colour, fragment)) => vec![ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::EndColour,
+            pos_begin: Some(fragment.pos_begin),
+            pos_end: Some(fragment.pos_end),
+            message: format!(
+                "String ends with colour '{{{}}}', but base ends with the default colour.",
+                colour
+            ),
+            suggestion: Some(String::from("Add '{BLACK}' at the end.")),
+        }],
+        None => Vec::new(),
+    }
+}
 
-        let val_trans = validate_string(&config, &base, Some(&base));
-        assert_eq!(val_trans.len(), 0);
+/**
+ * Validate whether a string starts with the required string commands.
+ *
+ * Gender definitions in front of the required commands are skipped.
+ *
+ * @param parsed The string to validate.
+ * @param required The names of the required leading commands, in order.
+ *
+ * @returns A list of error messages, if the required commands are missing.
+ */
+pub fn validate_required_prefix(parsed: &ParsedString, required: &[&str]) -> Vec<ValidationError> {
+    let mut fragments = parsed
+        .fragments
+        .iter()
+        .skip_while(|f| matches!(f.content, FragmentContent::Gender(_)));
+    for name in required {
+        let fragment = fragments.next();
+        let found = fragment.is_some_and(|f| match &f.content {
+            FragmentContent::Command(cmd) => cmd.name == *name,
+            _ => false,
+        });
+        if !found {
+            let expected: String = required.iter().map(|n| format!("{{{}}}", n)).collect();
+            return vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::RequiredPrefix,
+                pos_begin: fragment.map(|f| f.pos_begin),
+                pos_end: fragment.map(|f| f.pos_end),
+                message: format!("String must start with '{}'.", expected),
+                suggestion: Some(format!("Add '{{{}}}'.", name)),
+            }];
+        }
     }
+    Vec::new()
+}
 
-    #[test]
-    fn test_validate_invalid() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 0,
-        };
-        let base = ParsedString::parse("{FOOBAR}").unwrap();
+/**
+ * Validate whether a translation is probably left untranslated.
+ *
+ * A translation identical to the base is only reported, if it contains translatable text.
+ * Strings consisting of only commands are often the same in all languages.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of error messages, if the translation is probably untranslated.
+ */
+pub fn validate_untranslated(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let has_text = test.fragments.iter().any(|f| match &f.content {
+        FragmentContent::Text(t) => t.chars().any(char::is_alphabetic),
+        _ => false,
+    });
+    if !has_text || !test.equals_ignoring_positions(base) {
+        return Vec::new();
+    }
+    vec![ValidationError {
+        severity: Severity::Warning,
+        kind: FindingKind::Untranslated,
+        pos_begin: None,
+        pos_end: None,
+        message: String::from("Translation is identical to the base language."),
+        suggestion: Some(String::from("Translate the text.")),
+    }]
+}
 
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 1);
-        assert_eq!(
-            val_base[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(8),
-                message: String::from("Unknown string command '{FOOBAR}'."),
-                suggestion: None,
+/**
+ * Validate whether gender choice lists contain adjacent identical items.
+ *
+ * The second item of each pair is reported, or the whole choice list if the span of the item
+ * is not known; see StringFragment::choice_item_spans.
+ *
+ * @param parsed The string to validate.
+ *
+ * @returns A list of warnings, one for each duplicate item.
+ */
+pub fn validate_duplicate_genders(parsed: &ParsedString) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Choice(choice) = &fragment.content {
+            if choice.name != "G" {
+                continue;
             }
-        );
+            let spans = fragment.choice_item_spans();
+            for (i, pair) in choice.choices.windows(2).enumerate() {
+                if pair[0] == pair[1] {
+                    let (begin, end) = spans
+                        .as_ref()
+                        .map_or((fragment.pos_begin, fragment.pos_end), |s| s[i + 1]);
+                    errors.push(ValidationError {
+                        severity: Severity::Warning,
+                        kind: FindingKind::DuplicateGenderChoice,
+                        pos_begin: Some(begin),
+                        pos_end: Some(end),
+                        message: format!(
+                            "Gender choice {} is identical to choice {}: '{}'.",
+                            i + 2,
+                            i + 1,
+                            pair[1]
+                        ),
+                        suggestion: None,
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
 
-        let val_trans = validate_string(&config, &base, Some(&base));
-        assert_eq!(val_trans.len(), 1);
-        assert_eq!(
-            val_trans[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: None,
-                pos_end: None,
-                message: String::from("Base language text is invalid."),
-                suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
+/**
+ * Validate whether string commands appear more often than the command metadata allows.
+ *
+ * The builtin COMMANDS have no limits, as OpenTTD allows any command to repeat. This check
+ * needs a custom registry, which implements CommandMeta::max_occurrences for the commands of a project.
+ *
+ * @param parsed The string to validate.
+ * @param meta The command metadata, with the limits per command.
+ *
+ * @returns A list of error messages, one for each occurrence beyond the maximum.
+ */
+pub fn validate_max_occurrences<M: CommandMeta + ?Sized>(
+    parsed: &ParsedString,
+    meta: &M,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            let count = counts.entry(cmd.name.as_str()).or_default();
+            *count += 1;
+            if let Some(max) = meta.max_occurrences(&cmd.name) {
+                if *count > max {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        kind: FindingKind::MaxOccurrences,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: format!(
+                            "Too many '{{{}}}' commands, at most {} allowed.",
+                            cmd.name, max
+                        ),
+                        suggestion: Some(String::from("Remove this command.")),
+                    });
+                }
             }
-        );
+        }
     }
+    errors
+}
 
-    #[test]
-    fn test_validate_positional() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 0,
-        };
-        let base = ParsedString::parse("{NUM}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+/// Get the commands at the start and at the end of a string, before and after any other content.
+/// Gender definitions and comments are skipped.
+fn get_boundary_commands(parsed: &ParsedString) -> (Vec<&StringFragment>, Vec<&StringFragment>) {
+    let is_command = |f: &&StringFragment| matches!(f.content, FragmentContent::Command(_));
+    let is_skipped = |f: &&StringFragment| {
+        matches!(
+            f.content,
+            FragmentContent::Gender(_) | FragmentContent::Comment(_)
+        )
+    };
+    let leading = parsed
+        .fragments
+        .iter()
+        .filter(|f| !is_skipped(f))
+        .take_while(is_command)
+        .collect();
+    let mut trailing: Vec<&StringFragment> = parsed
+        .fragments
+        .iter()
+        .rev()
+        .filter(|f| !is_skipped(f))
+        .take_while(is_command)
+        .collect();
+    trailing.reverse();
+    (leading, trailing)
+}
 
+/**
+ * Validate whether a string starts or ends with a choice list, which refers to a parameter implicitly.
+ *
+ * For example '{P a b}' at the end of a string refers to the last parameter, which is easily lost
+ * when the string is reordered. Choice lists without any parameter to refer to are reported by
+ * validate_orphaned_choices instead.
+ *
+ * @param parsed The string to validate.
+ * @param severity The severity to report the choice lists with.
+ *
+ * @returns A list of error messages, one for each choice list at the start or end of the string.
+ */
+pub fn validate_boundary_choices(
+    parsed: &ParsedString,
+    severity: Severity,
+) -> Vec<ValidationError> {
+    let fragments: Vec<&StringFragment> = parsed
+        .fragments
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.content,
+                FragmentContent::Gender(_) | FragmentContent::Comment(_)
+            )
+        })
+        .collect();
+    let boundary = |fragment: &StringFragment| {
+        if fragments
+            .first()
+            .is_some_and(|f| std::ptr::eq(*f, fragment))
         {
-            let trans = ParsedString::parse("{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{FOOBAR}{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(8),
-                    message: String::from("Unknown string command '{FOOBAR}'."),
-                    suggestion: None,
-                }
-            );
+            Some("start")
+        } else if fragments.last().is_some_and(|f| std::ptr::eq(*f, fragment)) {
+            Some("end")
+        } else {
+            None
         }
-        {
-            let trans = ParsedString::parse("{1:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(7),
-                    message: String::from("There is no parameter in position 1, found '{NUM}'."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{0:NUM}' is missing."),
-                    suggestion: None,
-                }
-            );
+    };
+
+    let positions: HashSet<usize> = parsed.parameters().iter().map(|p| p.index).collect();
+    parsed
+        .choice_refs()
+        .into_iter()
+        .filter(|(_, choice, ref_pos)| {
+            choice.indexref.is_none() && ref_pos.is_some_and(|p| positions.contains(&p))
+        })
+        .filter_map(|(fragment, choice, _)| {
+            let what = boundary(fragment)?;
+            Some(ValidationError {
+                severity,
+                kind: FindingKind::BoundaryChoice,
+                pos_begin: Some(fragment.pos_begin),
+                pos_end: Some(fragment.pos_end),
+                message: format!(
+                    "Choice list '{{{}}}' at the {} of the string refers to a parameter implicitly.",
+                    choice.name, what
+                ),
+                suggestion: Some(String::from("Add a position reference.")),
+            })
+        })
+        .collect()
+}
+
+/**
+ * Validate whether a translation starts and ends with the same commands as the base.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of warnings, for the start and the end of the string.
+ */
+pub fn validate_boundary_commands(
+    test: &ParsedString,
+    base: &ParsedString,
+) -> Vec<ValidationError> {
+    let names = |fragments: &[&StringFragment]| -> Vec<String> {
+        fragments
+            .iter()
+            .filter_map(|f| match &f.content {
+                FragmentContent::Command(cmd) => Some(cmd.name.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    let describe = |names: &[String]| -> String {
+        if names.is_empty() {
+            String::from("no commands")
+        } else {
+            let list: String = names.iter().map(|n| format!("{{{}}}", n)).collect();
+            format!("'{}'", list)
         }
-        {
-            let trans = ParsedString::parse("{COMMA}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(7),
-                    message: String::from("Expected '{0:NUM}', found '{COMMA}'."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{0:NUM}' is missing."),
-                    suggestion: None,
+    };
+
+    let mut errors = Vec::new();
+    let (test_leading, test_trailing) = get_boundary_commands(test);
+    let (base_leading, base_trailing) = get_boundary_commands(base);
+    for (what, test_cmds, base_cmds) in [
+        ("starts", test_leading, base_leading),
+        ("ends", test_trailing, base_trailing),
+    ] {
+        let test_names = names(&test_cmds);
+        let base_names = names(&base_cmds);
+        if test_names != base_names {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::BoundaryCommands,
+                pos_begin: test_cmds.first().map(|f| f.pos_begin),
+                pos_end: test_cmds.last().map(|f| f.pos_end),
+                message: format!(
+                    "String {} with {}, but base {} with {}.",
+                    what,
+                    describe(&test_names),
+                    what,
+                    describe(&base_names)
+                ),
+                suggestion: None,
+            });
+        }
+    }
+    errors
+}
+
+/// Get the positions of all line breaks: newline characters and '{}' commands.
+fn get_line_breaks(parsed: &ParsedString) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    for fragment in &parsed.fragments {
+        match &fragment.content {
+            FragmentContent::Text(text) => {
+                for (i, c) in text.chars().enumerate() {
+                    if c == '\n' {
+                        result.push((fragment.pos_begin + i, fragment.pos_begin + i + 1));
+                    }
                 }
-            );
+            }
+            FragmentContent::Command(cmd) if cmd.name.is_empty() => {
+                result.push((fragment.pos_begin, fragment.pos_end));
+            }
+            _ => (),
         }
-        {
-            let trans = ParsedString::parse("{0:NUM}{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
+    }
+    result
+}
+
+/**
+ * Validate whether a translation has as many line breaks as the base.
+ *
+ * Both newline characters and '{}' count as line break.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of warnings. Extra line breaks are reported individually.
+ */
+pub fn validate_newlines(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let test_breaks = get_line_breaks(test);
+    let base_count = get_line_breaks(base).len();
+    if test_breaks.len() < base_count {
+        return vec![ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::LineBreaks,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "String has {} line breaks, but base has {}; {} missing.",
+                test_breaks.len(),
+                base_count,
+                base_count - test_breaks.len()
+            ),
+            suggestion: None,
+        }];
+    }
+    test_breaks[base_count..]
+        .iter()
+        .map(|(pos_begin, pos_end)| ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::LineBreaks,
+            pos_begin: Some(*pos_begin),
+            pos_end: Some(*pos_end),
+            message: format!(
+                "String has {} line breaks, but base has {}; {} extra.",
+                test_breaks.len(),
+                base_count,
+                test_breaks.len() - base_count
+            ),
+            suggestion: Some(String::from("Remove this line break.")),
+        })
+        .collect()
+}
+
+/**
+ * Validate whether all choice lists refer to a parameter.
+ *
+ * Plural lists refer to the previous parameter, gender lists to the next one, unless a position is given.
+ *
+ * @param parsed The string to validate.
+ *
+ * @returns A list of error messages, one for each choice list without parameter.
+ */
+pub fn validate_orphaned_choices(parsed: &ParsedString) -> Vec<ValidationError> {
+    let positions: HashSet<usize> = parsed.parameters().iter().map(|p| p.index).collect();
+    parsed
+        .choice_refs()
+        .into_iter()
+        .filter(|(_, _, ref_pos)| !ref_pos.is_some_and(|p| positions.contains(&p)))
+        .map(|(fragment, choice, _)| ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::OrphanedChoice,
+            pos_begin: Some(fragment.pos_begin),
+            pos_end: Some(fragment.pos_end),
+            message: format!(
+                "Choice list '{{{}}}' does not refer to any parameter.",
+                choice.name
+            ),
+            suggestion: Some(String::from("Remove the choice list.")),
+        })
+        .collect()
+}
+
+/**
+ * Validate whether plural and gender lists refer to different parameters.
+ *
+ * Plurals usually depend on a number, and genders on a noun, so a plural and a gender list
+ * referring to the same parameter is often a mistake.
+ *
+ * @param parsed The string to validate.
+ *
+ * @returns A list of warnings, for both lists of each plural and gender list referring to the same parameter.
+ */
+pub fn validate_shared_choice_refs(parsed: &ParsedString) -> Vec<ValidationError> {
+    let refs = parsed.choice_refs();
+    let mut errors = Vec::new();
+    for (plural, _, plural_pos) in refs.iter().filter(|(_, c, _)| c.name == "P") {
+        for (gender, _, gender_pos) in refs.iter().filter(|(_, c, _)| c.name == "G") {
+            let Some(ref_pos) = plural_pos.filter(|p| Some(*p) == *gender_pos) else {
+                continue;
+            };
+            for fragment in [plural, gender] {
+                errors.push(ValidationError {
                     severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from(
-                        "String command '{0:NUM}': expected 1 times, found 2 times."
+                    kind: FindingKind::SharedChoiceReference,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: format!(
+                        "Plural list '{{P}}' and gender list '{{G}}' both refer to position {}.",
+                        ref_pos
                     ),
                     suggestion: None,
-                }
-            );
+                });
+            }
         }
     }
+    errors
+}
 
-    #[test]
-    fn test_validate_front() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![String::from("a"), String::from("b")],
-            plural_count: 0,
+/**
+ * Validate whether a translation adds or removes case selections, compared to the base.
+ *
+ * Languages with cases often need them where the base has none, so this check is optional.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of warnings, one for each added or removed case selection.
+ */
+pub fn validate_case_changes(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let mut base_cases = HashMap::new();
+    for (pos, cmd) in base.indexed_commands() {
+        base_cases.entry(pos).or_insert(cmd.case.as_deref());
+    }
+
+    let mut errors = Vec::new();
+    for (slot, (pos, cmd)) in test.parameters().iter().zip(test.indexed_commands()) {
+        let Some(base_case) = base_cases.get(&pos) else {
+            continue;
         };
-        let base = ParsedString::parse("{BIG_FONT}foo{NUM}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let message = match (base_case, &cmd.case) {
+            (None, Some(case)) => format!(
+                "Case '.{}' is added to position {}, but base has no case.",
+                case, pos
+            ),
+            (Some(case), None) => format!(
+                "Case '.{}' of position {} is removed, compared to base.",
+                case, pos
+            ),
+            _ => continue,
+        };
+        errors.push(ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::CaseChange,
+            pos_begin: Some(slot.pos_begin),
+            pos_end: Some(slot.pos_end),
+            message,
+            suggestion: None,
+        });
+    }
+    errors
+}
 
-        {
-            let trans = ParsedString::parse("{G=a}{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{G=a}{G=a}{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(5),
-                    pos_end: Some(10),
-                    message: String::from("Duplicate gender definition."),
-                    suggestion: Some(String::from("Remove the second '{G=...}'.")),
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{BIG_FONT}{G=a}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(10),
-                    pos_end: Some(15),
-                    message: String::from("Gender definitions must be at the front."),
-                    suggestion: Some(String::from(
-                        "Move '{G=...}' to the front of the translation."
-                    )),
+/// Get the kind of parameter at each position, guessed from the command names.
+/// Commands of which the kind cannot be guessed are assumed to not take a parameter.
+fn get_inferred_kinds(parsed: &ParsedString) -> Vec<(usize, ParamKind, &StringFragment)> {
+    let mut result = Vec::new();
+    let mut pos = 0;
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if let Some(kind) = infer_param_kind(&cmd.name) {
+                if let Some(index) = cmd.index {
+                    pos = index;
                 }
-            );
+                result.push((pos, kind, fragment));
+                pos += 1;
+            }
         }
-        {
-            let trans = ParsedString::parse("foo{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
+    }
+    result
+}
+
+/**
+ * Validate whether the translation formats each parameter as the same kind as the base.
+ *
+ * This is a best-effort check, which guesses the kinds from the command names alone
+ * (see infer_param_kind), so it works without command metadata. Commands which are not
+ * recognized are ignored, which can shift the positions of the commands after them.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of error messages, one for each parameter of a different kind.
+ */
+pub fn validate_inferred_kinds(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let mut base_kinds = HashMap::new();
+    for (pos, kind, _) in get_inferred_kinds(base) {
+        base_kinds.entry(pos).or_insert(kind);
+    }
+
+    let mut errors = Vec::new();
+    for (pos, kind, fragment) in get_inferred_kinds(test) {
+        let Some(base_kind) = base_kinds.get(&pos) else {
+            continue;
+        };
+        if *base_kind != kind {
+            errors.push(ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ParameterKind,
+                pos_begin: Some(fragment.pos_begin),
+                pos_end: Some(fragment.pos_end),
+                message: format!(
+                    "Position {} looks like a {:?} parameter, but base uses a {:?} parameter.",
+                    pos, kind, base_kind
+                ),
+                suggestion: None,
+            });
         }
-        {
-            let trans = ParsedString::parse("foo{G=a}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(3),
-                    pos_end: Some(8),
-                    message: String::from("Gender definitions must be at the front."),
-                    suggestion: Some(String::from(
-                        "Move '{G=...}' to the front of the translation."
-                    )),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{BIG_FONT}' is missing."),
-                    suggestion: None,
-                }
-            );
+    }
+    errors
+}
+
+/**
+ * Validate whether the translation uses every parameter of the base.
+ *
+ * Dropping a parameter is sometimes intentional, so this only reports warnings.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of warnings, one for each position of the base which the translation does not use.
+ */
+pub fn validate_unused_parameters(
+    test: &ParsedString,
+    base: &ParsedString,
+) -> Vec<ValidationError> {
+    let used: HashSet<usize> = test.indexed_commands().map(|(pos, _)| pos).collect();
+    let mut unused = BTreeMap::new();
+    for (pos, cmd) in base.indexed_commands() {
+        if !used.contains(&pos) {
+            unused.entry(pos).or_insert(cmd);
         }
     }
+    unused
+        .into_iter()
+        .map(|(pos, cmd)| ValidationError {
+            severity: Severity::Warning,
+            kind: FindingKind::UnusedParameter,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "Parameter {} of base is not used, its value will not be shown.",
+                pos
+            ),
+            suggestion: Some(format!("Add '{{{}:{}}}'.", pos, cmd.name)),
+        })
+        .collect()
+}
 
-    #[test]
-    fn test_validate_position_references() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
-            plural_count: 2,
-        };
-        let base = ParsedString::parse("{RED}{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+/**
+ * Validate whether explicit positions of the translation are shifted by one, compared to the base.
+ *
+ * A command like '{2:STRING}', where the base has no '{STRING}' at position 2 but does at
+ * position 1 or 3, is likely a typo; all following parameters then shift as well. Reordered
+ * parameters, which keep their positions, are not reported.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list of error messages, one for each shifted position.
+ */
+pub fn validate_index_shifts(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let base_names: HashMap<usize, &str> = base
+        .indexed_commands()
+        .map(|(pos, cmd)| (pos, cmd.name.as_str()))
+        .collect();
+    let test_names: HashSet<(usize, &str)> = test
+        .indexed_commands()
+        .map(|(pos, cmd)| (pos, cmd.name.as_str()))
+        .collect();
 
-        {
-            let trans = ParsedString::parse("{RED}{1:STRING.x}{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
+    let mut errors = Vec::new();
+    for (slot, (pos, cmd)) in test.parameters().iter().zip(test.indexed_commands()) {
+        if cmd.index.is_none() || base_names.get(&pos) == Some(&cmd.name.as_str()) {
+            continue;
         }
-        {
-            let trans = ParsedString::parse("{2:RED}{1:STRING.z}{0:NUM.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
+        let shifted = [pos.checked_sub(1), Some(pos + 1)]
+            .into_iter()
+            .flatten()
+            .find(|p| {
+                base_names.get(p) == Some(&cmd.name.as_str())
+                    && !test_names.contains(&(*p, cmd.name.as_str()))
+            });
+        if let Some(base_pos) = shifted {
+            errors.push(ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::IndexShift,
+                pos_begin: Some(slot.pos_begin),
+                pos_end: Some(slot.pos_end),
+                message: format!(
+                    "'{{{}:{}}}' uses position {}, but base uses position {} for '{{{}}}'.",
+                    pos, cmd.name, pos, base_pos, cmd.name
+                ),
+                suggestion: Some(format!("Use '{{{}:{}}}'.", base_pos, cmd.name)),
+            });
+        }
+    }
+    errors
+}
+
+/**
+ * Validate whether the strings referenced via '{STRING:ID}' exist.
+ *
+ * @param parsed The string to validate.
+ * @param known_ids The ids of all strings which can be referenced.
+ *
+ * @returns A list of error messages, one for each unknown reference.
+ */
+pub fn validate_string_refs(
+    parsed: &ParsedString,
+    known_ids: &HashSet<String>,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::StringRef { id } = &fragment.content {
+            if !known_ids.contains(id) {
+                errors.push(ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownStringReference,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: format!("Referenced string '{}' does not exist.", id),
+                    suggestion: None,
+                });
+            }
+        }
+    }
+    errors
+}
+
+/**
+ * Validate whether a string fits in a maximum number of characters.
+ *
+ * @param parsed The string to validate.
+ * @param max The maximum number of characters.
+ * @param plain_text Whether to only count the characters of the text, instead of the whole string including commands.
+ *
+ * @returns A list of error messages, if the string is too long.
+ */
+pub fn validate_max_chars(
+    parsed: &ParsedString,
+    max: usize,
+    plain_text: bool,
+) -> Vec<ValidationError> {
+    let len = if plain_text {
+        parsed
+            .fragments
+            .iter()
+            .map(|f| match &f.content {
+                FragmentContent::Text(t) => t.chars().count(),
+                _ => 0,
+            })
+            .sum()
+    } else {
+        parsed.compiled_len()
+    };
+    max_length_error(len, max, "characters")
+}
+
+fn max_length_error(len: usize, max: usize, unit: &str) -> Vec<ValidationError> {
+    if len <= max {
+        return Vec::new();
+    }
+    vec![ValidationError {
+        severity: Severity::Error,
+        kind: FindingKind::MaxLength,
+        pos_begin: None,
+        pos_end: None,
+        message: format!(
+            "String is {} {} long, which is {} more than the maximum of {}.",
+            len,
+            unit,
+            len - max,
+            max
+        ),
+        suggestion: Some(String::from("Shorten the string.")),
+    }]
+}
+
+/// Number of characters in a text, counting emoji as a single glyph.
+/// Emoji joined by a zero width joiner, flags and emoji with modifiers each count as one.
+fn glyph_count(text: &str) -> usize {
+    let mut count = 0;
+    let mut joined = false;
+    let mut flag = false;
+    for c in text.chars() {
+        match c {
+            '\u{200D}' => joined = true,
+            '\u{FE00}'..='\u{FE0F}'
+            | '\u{20E3}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}' => (),
+            '\u{1F1E6}'..='\u{1F1FF}' => {
+                // regional indicators form a flag in pairs
+                if !flag && !joined {
+                    count += 1;
+                }
+                flag = !flag;
+                joined = false;
+            }
+            _ => {
+                if !joined {
+                    count += 1;
+                }
+                joined = false;
+                flag = false;
+            }
+        }
+    }
+    count
+}
+
+/**
+ * Validate whether a string fits in a maximum number of glyphs, counting emoji as one glyph.
+ *
+ * Like validate_max_chars, but for fields which draw emoji sequences, like a family joined by
+ * zero width joiners, as a single glyph.
+ *
+ * @param parsed The string to validate.
+ * @param max The maximum number of glyphs.
+ * @param plain_text Whether to only count the glyphs of the text, instead of the whole string including commands.
+ *
+ * @returns A list of error messages, if the string is too long.
+ */
+pub fn validate_max_glyphs(
+    parsed: &ParsedString,
+    max: usize,
+    plain_text: bool,
+) -> Vec<ValidationError> {
+    let len = parsed
+        .fragments
+        .iter()
+        .map(|f| match &f.content {
+            FragmentContent::Text(t) => glyph_count(t),
+            _ if plain_text => 0,
+            content => content.compile().chars().count(),
+        })
+        .sum();
+    max_length_error(len, max, "glyphs")
+}
+
+/**
+ * Validate whether a string has more string commands than a sane string would have.
+ *
+ * This is a cheap guard against corrupt strings; all commands, choice lists and gender
+ * definitions count, but text and comments do not.
+ *
+ * @param parsed The string to validate.
+ * @param max The maximum number of commands.
+ *
+ * @returns A list of error messages, if the string has too many commands.
+ */
+pub fn validate_max_commands(parsed: &ParsedString, max: usize) -> Vec<ValidationError> {
+    let count = parsed
+        .fragments
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.content,
+                FragmentContent::Text(_) | FragmentContent::Comment(_)
+            )
+        })
+        .count();
+    if count <= max {
+        return Vec::new();
+    }
+    vec![ValidationError {
+        severity: Severity::Error,
+        kind: FindingKind::MaxCommands,
+        pos_begin: None,
+        pos_end: None,
+        message: format!(
+            "String has {} commands, which is more than the maximum of {}.",
+            count, max
+        ),
+        suggestion: None,
+    }]
+}
+
+/**
+ * Validate whether the text of a string contains tab characters.
+ *
+ * @param parsed The string to validate.
+ * @param severity The severity to report tabs with.
+ *
+ * @returns A list of error messages, one for each tab.
+ */
+pub fn validate_tabs(parsed: &ParsedString, severity: Severity) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Text(text) = &fragment.content {
+            for (i, c) in text.chars().enumerate() {
+                if c == '\t' {
+                    errors.push(ValidationError {
+                        severity,
+                        kind: FindingKind::TabCharacter,
+                        pos_begin: Some(fragment.pos_begin + i),
+                        pos_end: Some(fragment.pos_begin + i + 1),
+                        message: String::from("Tab character found."),
+                        suggestion: Some(String::from("Replace the tab with a space.")),
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+/**
+ * Validate whether the empty string command '{}' is used, where it is not allowed.
+ *
+ * @param parsed The string to validate.
+ * @param allowed Whether '{}' is allowed in the context of this string.
+ *
+ * @returns A list of error messages, one for each disallowed '{}'.
+ */
+pub fn validate_empty_command(parsed: &ParsedString, allowed: bool) -> Vec<ValidationError> {
+    if allowed {
+        return Vec::new();
+    }
+    parsed
+        .fragments
+        .iter()
+        .filter(|f| matches!(&f.content, FragmentContent::Command(cmd) if cmd.name.is_empty()))
+        .map(|f| ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::EmptyCommand,
+            pos_begin: Some(f.pos_begin),
+            pos_end: Some(f.pos_end),
+            message: String::from("String command '{}' is not allowed in this string."),
+            suggestion: Some(String::from("Remove this command.")),
+        })
+        .collect()
+}
+
+/**
+ * Validate whether colour commands are used, where they are not allowed.
+ *
+ * @param parsed The string to validate.
+ * @param allowed Whether colours are allowed in the context of this string.
+ *
+ * @returns A list of error messages, one for each disallowed colour command.
+ */
+pub fn validate_colours(parsed: &ParsedString, allowed: bool) -> Vec<ValidationError> {
+    if allowed {
+        return Vec::new();
+    }
+    get_colours(parsed)
+        .into_iter()
+        .map(|(name, f)| ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::ColourNotAllowed,
+            pos_begin: Some(f.pos_begin),
+            pos_end: Some(f.pos_end),
+            message: format!(
+                "Colour command '{{{}}}' is not allowed in this string.",
+                name
+            ),
+            suggestion: Some(String::from("Remove this command.")),
+        })
+        .collect()
+}
+
+fn get_colours(parsed: &ParsedString) -> Vec<(&str, &StringFragment)> {
+    parsed
+        .fragments
+        .iter()
+        .filter_map(|f| match &f.content {
+            FragmentContent::Command(cmd)
+                if COMMANDS
+                    .iter()
+                    .any(|ci| ci.name == cmd.name && ci.category == Category::COLOUR) =>
+            {
+                Some((cmd.name.as_str(), f))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/**
+ * Validate whether a translation uses exactly the same colours as the base, in the same order.
+ *
+ * This is stricter than the normal validation, which only checks the colours are a subset of the base.
+ *
+ * @param test The translation to validate.
+ * @param base The base string.
+ *
+ * @returns A list with at most one error message, for the first colour which differs from the base.
+ */
+pub fn validate_colour_order(test: &ParsedString, base: &ParsedString) -> Vec<ValidationError> {
+    let test_colours = get_colours(test);
+    let base_colours = get_colours(base);
+
+    for i in 0..test_colours.len().max(base_colours.len()) {
+        let error = match (test_colours.get(i), base_colours.get(i)) {
+            (Some((name, f)), Some((base_name, _))) if name != base_name => ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: Some(f.pos_begin),
+                pos_end: Some(f.pos_end),
+                message: format!(
+                    "Colour '{{{}}}' is used where base uses '{{{}}}'.",
+                    name, base_name
+                ),
+                suggestion: Some(format!("Use '{{{}}}' instead.", base_name)),
+            },
+            (Some((name, f)), None) => ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: Some(f.pos_begin),
+                pos_end: Some(f.pos_end),
+                message: format!("Colour '{{{}}}' is not used at this point in base.", name),
+                suggestion: Some(String::from("Remove this command.")),
+            },
+            (None, Some((base_name, _))) => ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: None,
+                pos_end: None,
+                message: format!("Colour '{{{}}}' of base is missing.", base_name),
+                suggestion: Some(format!("Add '{{{}}}'.", base_name)),
+            },
+            _ => continue,
+        };
+        return vec![error];
+    }
+    Vec::new()
+}
+
+/**
+ * Validate whether a choice list contains an item which is much longer than the other items.
+ *
+ * This often means text ended up in the wrong item.
+ * Items are reported at their own span, if it is known; see StringFragment::choice_item_spans.
+ * Otherwise the whole choice list is reported.
+ *
+ * @param parsed The string to validate.
+ * @param multiple How many times the median item length an item may be, before it is reported.
+ *
+ * @returns A list of warnings, one for each unusually long item.
+ */
+pub fn validate_long_choice_items(parsed: &ParsedString, multiple: f64) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        let FragmentContent::Choice(choice) = &fragment.content else {
+            continue;
+        };
+        let mut lengths: Vec<usize> = choice.choices.iter().map(|c| c.chars().count()).collect();
+        lengths.sort();
+        let Some(&median) = lengths.get(lengths.len().saturating_sub(1) / 2) else {
+            continue;
+        };
+        if median == 0 {
+            continue;
+        }
+        let spans = fragment.choice_item_spans();
+        for (i, item) in choice.choices.iter().enumerate() {
+            if item.chars().count() as f64 > median as f64 * multiple {
+                let (begin, end) = spans
+                    .as_ref()
+                    .map_or((fragment.pos_begin, fragment.pos_end), |s| s[i]);
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::LongChoiceItem,
+                    pos_begin: Some(begin),
+                    pos_end: Some(end),
+                    message: format!(
+                        "Choice item '{}' is much longer than the other items of '{{{}}}'.",
+                        item, choice.name
+                    ),
+                    suggestion: Some(String::from("Check whether the text belongs in this item.")),
+                });
+            }
+        }
+    }
+    errors
+}
+
+/// Invisible characters, which are easily inserted by accident.
+/// The zero width (non-)joiners are not listed: scripts like Persian and Hindi, and emoji, need them.
+pub const INVISIBLE_CHARS: &[char] = &[
+    '\u{FEFF}', // byte order mark, zero width no-break space
+    '\u{200B}', // zero width space
+    '\u{2060}', // word joiner
+];
+
+/**
+ * Validate whether the text of a string contains invisible characters.
+ *
+ * @param parsed The string to validate.
+ * @param chars The characters to report, for example INVISIBLE_CHARS.
+ *
+ * @returns A list of error messages, one for each invisible character.
+ */
+pub fn validate_invisible_chars(parsed: &ParsedString, chars: &[char]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Text(text) = &fragment.content {
+            for (i, c) in text.chars().enumerate() {
+                if chars.contains(&c) {
+                    errors.push(ValidationError {
+                        severity: Severity::Warning,
+                        kind: FindingKind::InvisibleCharacter,
+                        pos_begin: Some(fragment.pos_begin + i),
+                        pos_end: Some(fragment.pos_begin + i + 1),
+                        message: format!("Invisible character U+{:04X} found.", c as u32),
+                        suggestion: Some(String::from("Remove this character.")),
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+static PAT_FORMAT_SPECIFIER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"%(?:\d+\$)?[-+#0]*\d*(?:\.\d+)?(?:hh|h|ll|l|z)?[sdiuxXfcp]\b").unwrap()
+});
+
+/**
+ * Validate whether the text of a string contains C-style format specifiers, like '%s'.
+ *
+ * These are usually copied from a different format; strings use string commands instead.
+ *
+ * @param parsed The string to validate.
+ *
+ * @returns A list of warnings, one for each format specifier.
+ */
+pub fn validate_format_specifiers(parsed: &ParsedString) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Text(text) = &fragment.content {
+            for m in PAT_FORMAT_SPECIFIER.find_iter(text) {
+                let pos_begin = fragment.pos_begin + text[..m.start()].chars().count();
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::FormatSpecifier,
+                    pos_begin: Some(pos_begin),
+                    pos_end: Some(pos_begin + m.as_str().chars().count()),
+                    message: format!("Format specifier '{}' found in text.", m.as_str()),
+                    suggestion: Some(String::from("Use a string command instead.")),
+                });
+            }
+        }
+    }
+    errors
+}
+
+fn normalize_string(meta: &dyn CommandMeta, dialect: &Dialect, parsed: &mut ParsedString) {
+    let mut parameters = HashMap::new();
+
+    let mut pos = 0;
+    for fragment in &mut parsed.fragments {
+        match &mut fragment.content {
+            FragmentContent::Command(cmd) => {
+                if let Some(info) = meta.get(dialect, &cmd.name) {
+                    if let Some(norm_name) = info.norm_name {
+                        // normalize name
+                        cmd.name = String::from(norm_name);
+                    }
+                    if info.arity() != 0 {
+                        if let Some(index) = cmd.index {
+                            pos = index;
+                        } else {
+                            // add missing indices
+                            cmd.index = Some(pos);
+                        }
+                        parameters.insert(pos, info);
+                        pos += 1;
+                    }
+                }
+            }
+            FragmentContent::Choice(cmd) => {
+                match cmd.name.as_str() {
+                    "P" => {
+                        if cmd.indexref.is_none() && pos > 0 {
+                            // add missing indices
+                            cmd.indexref = Some(pos - 1);
+                        }
+                    }
+                    "G" => {
+                        if cmd.indexref.is_none() {
+                            // add missing indices
+                            cmd.indexref = Some(pos);
+                        }
+                    }
+                    _ => panic!(),
+                };
+            }
+            _ => (),
+        }
+    }
+
+    for fragment in &mut parsed.fragments {
+        if let FragmentContent::Choice(cmd) = &mut fragment.content {
+            if let Some(ref_info) = cmd.indexref.and_then(|pos| parameters.get(&pos)) {
+                if cmd.indexsubref == ref_info.def_plural_subindex.or(Some(0)) {
+                    // remove subindex, if default
+                    cmd.indexsubref = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ParameterInfo;
+
+    #[test]
+    fn test_sanitize() {
+        let mut s1 = String::from("");
+        let mut s2 = String::from(" a b c ");
+        let mut s3 = String::from("\0a\tb\rc\r\n");
+        let mut s4 = String::from("abc\u{b3}");
+        remove_ascii_ctrl(&mut s1);
+        remove_ascii_ctrl(&mut s2);
+        remove_ascii_ctrl(&mut s3);
+        remove_ascii_ctrl(&mut s4);
+        assert_eq!(s1, String::from(""));
+        assert_eq!(s2, String::from(" a b c "));
+        assert_eq!(s3, String::from(" a b c  "));
+        assert_eq!(s4, String::from("abc\u{b3}"));
+        remove_trailing_blanks(&mut s1);
+        remove_trailing_blanks(&mut s2);
+        remove_trailing_blanks(&mut s3);
+        remove_trailing_blanks(&mut s4);
+        assert_eq!(s1, String::from(""));
+        assert_eq!(s2, String::from(" a b c"));
+        assert_eq!(s3, String::from(" a b c"));
+        assert_eq!(s4, String::from("abc\u{b3}"));
+    }
+
+    #[test]
+    fn test_for_language() {
+        let config = LanguageConfig::for_language("pl_PL").unwrap();
+        assert_eq!(config.dialect, Dialect::OPENTTD);
+        assert_eq!(config.plural_count, 3);
+        assert_eq!(config.genders, vec!["m", "f", "n"]);
+        assert_eq!(config.cases.len(), 6);
+
+        let config = LanguageConfig::for_language("en_GB").unwrap();
+        assert_eq!(config.plural_count, 2);
+        assert!(config.genders.is_empty());
+        assert!(config.cases.is_empty());
+
+        assert!(LanguageConfig::for_language("xx_XX").is_none());
+    }
+
+    #[test]
+    fn test_signature_empty() {
+        let parsed = ParsedString::parse("").unwrap();
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert!(sig.parameters.is_empty());
+        assert!(sig.nonpositional_count.is_empty());
+    }
+
+    #[test]
+    fn test_signature_pos() {
+        let parsed = ParsedString::parse("{P a b}{RED}{NUM}{NBSP}{MONO_FONT}{5:STRING.foo}{RED}{2:STRING3.bar}{RAW_STRING}{3:RAW_STRING}{G c d}").unwrap();
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 4);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "NUM");
+        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&5).unwrap().0.name, "STRING");
+        assert_eq!(sig.parameters.get(&5).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&2).unwrap().0.name, "STRING3");
+        assert_eq!(sig.parameters.get(&2).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&3).unwrap().0.name, "RAW_STRING");
+        assert_eq!(sig.parameters.get(&3).unwrap().1, 2);
+        assert_eq!(sig.nonpositional_count.len(), 3);
+        assert_eq!(
+            sig.nonpositional_count.get("RED"),
+            Some(&(Occurence::NONZERO, 2))
+        );
+        assert_eq!(
+            sig.nonpositional_count.get("MONO_FONT"),
+            Some(&(Occurence::EXACT, 1))
+        );
+        assert_eq!(
+            sig.nonpositional_count.get("NBSP"),
+            Some(&(Occurence::ANY, 1))
+        );
+    }
+
+    #[test]
+    fn test_signature_dialect() {
+        let parsed = ParsedString::parse("{RAW_STRING}").unwrap();
+
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 1);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "RAW_STRING");
+        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
+        assert_eq!(sig.nonpositional_count.len(), 0);
+
+        let err = get_signature(&COMMANDS, &Dialect::NEWGRF, &parsed)
+            .err()
+            .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(12),
+                message: String::from("Unknown string command '{RAW_STRING}'."),
+                suggestion: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_signature_unknown() {
+        let parsed = ParsedString::parse("{FOOBAR}").unwrap();
+        let err = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed)
+            .err()
+            .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(8),
+                message: String::from("Unknown string command '{FOOBAR}'."),
+                suggestion: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_signature_arity() {
+        let parsed = ParsedString::parse("{STRING2}{NUM}").unwrap();
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 2);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.arity(), 3);
+        assert_eq!(sig.parameters.get(&1).unwrap().0.name, "NUM");
+        assert_eq!(sig.parameters.get(&1).unwrap().0.arity(), 1);
+
+        const PAIR: CommandInfo = CommandInfo {
+            name: "MY_PAIR",
+            norm_name: None,
+            dialects: &[Dialect::OPENTTD],
+            category: Category::PARAMETER,
+            occurence: Occurence::EXACT,
+            allow_case: false,
+            def_plural_subindex: Some(1),
+            parameters: &[
+                ParameterInfo {
+                    allow_plural: false,
+                    allow_gender: true,
+                },
+                ParameterInfo {
+                    allow_plural: true,
+                    allow_gender: false,
+                },
+            ],
+        };
+        struct Pair;
+        impl CommandMeta for Pair {
+            fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+                match name {
+                    "MY_PAIR" => Some(&PAIR),
+                    _ => CommandMeta::get(COMMANDS, dialect, name),
+                }
+            }
+        }
+        assert_eq!(Pair.arity(&Dialect::OPENTTD, "MY_PAIR"), 2);
+
+        // the second parameter is addressed by subindex, and does not take a position
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let val_base = validate_base_with_commands(&config, &Pair, "{MY_PAIR} {P a b} {NUM}");
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized.unwrap(),
+            "{0:MY_PAIR} {P 0 a b} {1:NUM}"
+        );
+        let val_base = validate_base_with_commands(&config, &Pair, "{MY_PAIR} {P 0:0 a b}");
+        assert_eq!(val_base.errors.len(), 1);
+        assert_eq!(
+            val_base.errors[0].message,
+            "'{P}' references position '0:0', but '{0:MY_PAIR}' does not allow plurals."
+        );
+    }
+
+    #[test]
+    fn test_signature_comment() {
+        let parsed = ParsedString::parse("{# note}{NUM}{# {RED}}").unwrap();
+        assert_eq!(
+            parsed.fragments,
+            vec![
+                StringFragment {
+                    pos_begin: 0,
+                    pos_end: 8,
+                    content: FragmentContent::Comment(String::from(" note")),
+                },
+                StringFragment {
+                    pos_begin: 8,
+                    pos_end: 13,
+                    content: FragmentContent::Command(StringCommand {
+                        index: None,
+                        name: String::from("NUM"),
+                        case: None,
+                    }),
+                },
+                StringFragment {
+                    pos_begin: 13,
+                    pos_end: 22,
+                    content: FragmentContent::Comment(String::from(" {RED}")),
+                },
+            ]
+        );
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 1);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "NUM");
+        assert!(sig.nonpositional_count.is_empty());
+    }
+
+    #[test]
+    fn test_validate_comments() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let val_base = validate_base(&config, "{# use {RED} here}{NUM} trains");
+        assert_eq!(val_base.errors.len(), 0);
+        assert_eq!(val_base.normalized.unwrap(), "{0:NUM} trains");
+
+        let val_trans = validate_translation(
+            &config,
+            "{NUM} trains",
+            "default",
+            "{NUM} treinen{# not {RED}}",
+        );
+        assert_eq!(val_trans.errors.len(), 0);
+        assert_eq!(val_trans.normalized.unwrap(), "{0:NUM} treinen");
+    }
+
+    #[test]
+    fn test_signature_skip() {
+        let parsed = ParsedString::parse("{SKIP}{NUM}").unwrap();
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 2);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "SKIP");
+        assert_eq!(sig.parameters.get(&1).unwrap().0.name, "NUM");
+        assert!(sig.nonpositional_count.is_empty());
+        assert_eq!(parsed.parameters()[1].index, 1);
+
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let trans = ParsedString::parse("{1:NUM}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&parsed)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_signature_font() {
+        let parsed = ParsedString::parse("{TINYFONT}{STRING}").unwrap();
+        let sig = get_signature(&COMMANDS, &Dialect::NEWGRF, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 1);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "STRING");
+        assert_eq!(
+            sig.nonpositional_count.get("TINY_FONT"),
+            Some(&(Occurence::EXACT, 1))
+        );
+        assert_eq!(parsed.parameters().len(), 1);
+
+        let config = LanguageConfig {
+            dialect: Dialect::NEWGRF,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let trans = ParsedString::parse("{TINY_FONT}{STRING}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&parsed)),
+            vec![]
+        );
+        let trans = ParsedString::parse("{BIGFONT}{STRING}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&parsed)),
+            vec![
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{TINY_FONT}' is missing."),
+                    suggestion: None,
+                },
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::UnexpectedCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{BIG_FONT}' is unexpected."),
+                    suggestion: Some(String::from("Remove this command.")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_index_policy() {
+        let parsed = ParsedString::parse("{0:NUM} {2:STRING}").unwrap();
+
+        let sig = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed)
+            .ok()
+            .unwrap();
+        assert_eq!(sig.parameters.len(), 2);
+
+        let err = get_signature(&COMMANDS, &Dialect::GAMESCRIPT, &parsed)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::SkippedPosition,
+                pos_begin: Some(8),
+                pos_end: Some(18),
+                message: String::from("Position 2 is used, but position 1 is not."),
+                suggestion: Some(String::from("Do not skip positions.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("{1:NUM} {0:STRING}").unwrap();
+        assert!(get_signature(&COMMANDS, &Dialect::GAMESCRIPT, &parsed).is_ok());
+    }
+
+    #[test]
+    fn test_signature_nonpos() {
+        let parsed = ParsedString::parse("{1:RED}").unwrap();
+        let err = get_signature(&COMMANDS, &Dialect::OPENTTD, &parsed)
+            .err()
+            .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::PositionNotAllowed,
+                pos_begin: Some(0),
+                pos_end: Some(7),
+                message: String::from("Command '{RED}' cannot have a position reference."),
+                suggestion: Some(String::from("Remove '1:'.")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("").unwrap();
+
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        let val_trans = validate_string(&config, &COMMANDS, &base, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_blank() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("Hello").unwrap();
+        let empty = ParsedString::parse("").unwrap();
+        let blank = ParsedString::parse(" \n{# todo}").unwrap();
+        let error = ValidationError {
+            severity: Severity::Error,
+            kind: FindingKind::EmptyTranslation,
+            pos_begin: None,
+            pos_end: None,
+            message: String::from("Translation is empty."),
+            suggestion: None,
+        };
+
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &empty, Some(&base)),
+            vec![error]
+        );
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &blank, Some(&base)).len(),
+            1
+        );
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &empty, Some(&empty)),
+            vec![]
+        );
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &empty, Some(&blank)),
+            vec![]
+        );
+        assert_eq!(validate_string(&config, &COMMANDS, &empty, None), vec![]);
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{FOOBAR}").unwrap();
+
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(
+            val_base[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(8),
+                message: String::from("Unknown string command '{FOOBAR}'."),
+                suggestion: None,
+            }
+        );
+
+        let val_trans = validate_string(&config, &COMMANDS, &base, Some(&base));
+        assert_eq!(val_trans.len(), 1);
+        assert_eq!(
+            val_trans[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidBase,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Base language text is invalid."),
+                suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_positional() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{NUM}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{FOOBAR}{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownCommand,
+                    pos_begin: Some(0),
+                    pos_end: Some(8),
+                    message: String::from("Unknown string command '{FOOBAR}'."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{1:NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownPosition,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("There is no parameter in position 1, found '{NUM}'."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{COMMA}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::ParameterMismatch,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("Expected '{0:NUM}', found '{COMMA}'."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{0:NUM}{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::CommandCount,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from(
+                        "String command '{0:NUM}': expected 1 times, found 2 times."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_front() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 0,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{BIG_FONT}foo{NUM}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{G=a}{G=a}{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::DuplicateGenderDefinition,
+                    pos_begin: Some(5),
+                    pos_end: Some(10),
+                    message: String::from("Duplicate gender definition."),
+                    suggestion: Some(String::from("Remove the second '{G=...}'.")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{BIG_FONT}{G=a}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::GenderDefinitionPosition,
+                    pos_begin: Some(10),
+                    pos_end: Some(15),
+                    message: String::from("Gender definitions must be at the front."),
+                    suggestion: Some(String::from(
+                        "Move '{G=...}' to the front of the translation."
+                    )),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("foo{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("foo{G=a}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::GenderDefinitionPosition,
+                    pos_begin: Some(3),
+                    pos_end: Some(8),
+                    message: String::from("Gender definitions must be at the front."),
+                    suggestion: Some(String::from(
+                        "Move '{G=...}' to the front of the translation."
+                    )),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{BIG_FONT}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_reorder() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{STRING} {NUM}").unwrap();
+
+        let trans = ParsedString::parse("{1:NUM} {0:STRING}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&base)),
+            vec![]
+        );
+
+        let trans = ParsedString::parse("{NUM} {STRING}").unwrap();
+        let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+        assert_eq!(
+            val_trans[0],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ParameterMismatch,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from("Expected '{0:STRING}', found '{NUM}'."),
+                suggestion: Some(String::from("Use '{1:NUM}' to reorder parameters.")),
+            }
+        );
+        assert_eq!(
+            val_trans[1],
+            ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ParameterMismatch,
+                pos_begin: Some(6),
+                pos_end: Some(14),
+                message: String::from("Expected '{1:NUM}', found '{STRING}'."),
+                suggestion: Some(String::from("Use '{0:STRING}' to reorder parameters.")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_caseless() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{NUM} {STRING} {COMMA}").unwrap();
+
+        let trans = ParsedString::parse("{NUM} {STRING.gen} {COMMA}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&base)),
+            vec![]
+        );
+
+        let trans = ParsedString::parse("{NUM.gen} {STRING.gen} {COMMA.gen}").unwrap();
+        assert_eq!(
+            validate_string(&config, &COMMANDS, &trans, Some(&base)),
+            vec![
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::CaseNotAllowed,
+                    pos_begin: Some(0),
+                    pos_end: Some(9),
+                    message: String::from("No case selection allowed for '{NUM}'."),
+                    suggestion: Some(String::from("Remove '.gen'.")),
+                },
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::CaseNotAllowed,
+                    pos_begin: Some(23),
+                    pos_end: Some(34),
+                    message: String::from("No case selection allowed for '{COMMA}'."),
+                    suggestion: Some(String::from("Remove '.gen'.")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_position_references() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{RED}{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{RED}{1:STRING.x}{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{2:RED}{1:STRING.z}{0:NUM.x}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::PositionNotAllowed,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("Command '{RED}' cannot have a position reference."),
+                    suggestion: Some(String::from("Remove '2:'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownCase,
+                    pos_begin: Some(7),
+                    pos_end: Some(19),
+                    message: String::from("Unknown case 'z'."),
+                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::CaseNotAllowed,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from("No case selection allowed for '{NUM}'."),
+                    suggestion: Some(String::from("Remove '.x'.")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G i j}{P i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 0 i j}{P 1 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(10),
+                    pos_end: Some(19),
+                    message: String::from(
+                        "'{G}' references position '0:0', but '{0:NUM}' does not allow genders."
+                    ),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from(
+                        "'{P}' references position '1:0', but '{1:STRING}' does not allow plurals."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 1:1 i j}{P 1:3 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 1:4 i j}{P 1:4 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(10),
+                    pos_end: Some(21),
+                    message: String::from(
+                        "'{G}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                    ),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(21),
+                    pos_end: Some(32),
+                    message: String::from(
+                        "'{P}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 2 i j}{P 2 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(10),
+                    pos_end: Some(19),
+                    message: String::from("'{G}' references position '2', which has no parameter."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from("'{P}' references position '2', which has no parameter."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{P i j}{NUM}{STRING.y}{G i j}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(5),
+                    pos_end: Some(12),
+                    message: String::from(
+                        "'{P}' references position '-1', which has no parameter."
+                    ),
+                    suggestion: Some(String::from("Add a position reference.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::InvalidChoiceReference,
+                    pos_begin: Some(27),
+                    pos_end: Some(34),
+                    message: String::from("'{G}' references position '2', which has no parameter."),
+                    suggestion: Some(String::from("Add a position reference.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_gender_ref() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_trans = validate_translation(&config, "{STRING}", "default", "{G=0}{STRING}");
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(val_trans.normalized, Some(String::from("{G=0}{0:STRING}")));
+
+        let val_trans = validate_translation(&config, "{STRING}", "default", "{G=1}{STRING}");
+        assert_eq!(
+            val_trans.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidChoiceReference,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from(
+                    "Gender definition references position 1, which has no parameter."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        let newgrf = LanguageConfig {
+            dialect: Dialect::NEWGRF,
+            ..config.clone()
+        };
+        let val_trans = validate_translation(&newgrf, "{STRING}", "default", "{G=0}{STRING}");
+        assert_eq!(
+            val_trans.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::PositionNotAllowed,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from("Gender definitions cannot reference a parameter."),
+                suggestion: Some(String::from("Use the name of a gender.")),
+            }]
+        );
+        let val_trans = validate_translation(&newgrf, "{STRING}", "default", "{G=m}{STRING}");
+        assert_eq!(val_trans.errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_relaxed_genders() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_base = validate_base(&config, "{G=n}Stadt");
+        assert_eq!(val_base.errors.len(), 1);
+        assert_eq!(val_base.errors[0].message, "Unknown gender 'n'.");
+
+        config.relaxed_genders = true;
+        let val_base = validate_base(&config, "{G=n}Stadt");
+        assert_eq!(val_base.errors, vec![]);
+
+        let val_base = validate_base(&config, "Stadt{G=n}");
+        assert_eq!(val_base.errors.len(), 1);
+        assert_eq!(
+            val_base.errors[0].message,
+            "Gender definitions must be at the front."
+        );
+
+        let val_base = validate_base(&config, "{G=n}{G=x}Stadt");
+        assert_eq!(val_base.errors.len(), 1);
+        assert_eq!(val_base.errors[0].message, "Duplicate gender definition.");
+
+        let val_trans =
+            validate_translation(&config, "{STRING} is", "default", "{G a b c} {STRING}");
+        assert_eq!(val_trans.errors.len(), 1);
+        assert_eq!(
+            val_trans.errors[0].message,
+            "Expected 2 gender choices, found 3."
+        );
+
+        config.genders = vec![];
+        let val_base = validate_base(&config, "{G=x}Stadt");
+        assert_eq!(val_base.errors, vec![]);
+        let val_trans =
+            validate_translation(&config, "{STRING} is", "default", "{G a b c} {STRING}");
+        assert_eq!(val_trans.errors, vec![]);
+
+        let val_base = validate_base(&config, "Stadt{G=n}");
+        assert_eq!(
+            val_base.errors[0].message,
+            "Gender definitions must be at the front."
+        );
+
+        config.relaxed_genders = false;
+        let val_base = validate_base(&config, "{G=x}Stadt");
+        assert_eq!(val_base.errors[0].message, "No gender definitions allowed.");
+    }
+
+    #[test]
+    fn test_validate_gender_source() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_base = validate_base(&config, "{G 0 m f} text");
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidChoiceReference,
+                pos_begin: Some(0),
+                pos_end: Some(9),
+                message: String::from(
+                    "'{G}' references a parameter, but the string has no parameters."
+                ),
+                suggestion: Some(String::from("Remove '{G ...}'.")),
+            }]
+        );
+
+        let val_base = validate_base(&config, "{G=m}{G m f} text");
+        assert_eq!(val_base.errors.len(), 1);
+
+        let val_base = validate_base(&config, "{G 0 m f} {STRING}");
+        assert_eq!(val_base.errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_choice_reference_type() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_base = validate_base(&config, "{STRING}{P 0 a b}");
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidChoiceReference,
+                pos_begin: Some(8),
+                pos_end: Some(17),
+                message: String::from(
+                    "'{P}' references position '0:0', but '{0:STRING}' does not allow plurals."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        let val_base = validate_base(&config, "{NUM}{STRING}{P 0 a b}{G 1 a b}");
+        assert_eq!(val_base.errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_choice_no_parameters() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_base = validate_base(&config, "{P 0 a b} text");
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::InvalidChoiceReference,
+                pos_begin: Some(0),
+                pos_end: Some(9),
+                message: String::from(
+                    "'{P}' references a parameter, but the string has no parameters."
+                ),
+                suggestion: Some(String::from("Remove '{P ...}'.")),
+            }]
+        );
+
+        let val_base = validate_base(&config, "{P a b} text");
+        assert_eq!(val_base.errors.len(), 1);
+        assert_eq!(
+            val_base.errors[0].message,
+            "'{P}' references a parameter, but the string has no parameters."
+        );
+
+        let val_base = validate_base(&config, "{NUM} {P 2 a b}");
+        assert_eq!(
+            val_base.errors[0].message,
+            "'{P}' references position '2', which has no parameter."
+        );
+    }
+
+    #[test]
+    fn test_validate_nochoices() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 1,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a}{G a}{STRING}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::GenderNotAllowed,
                     pos_begin: Some(0),
-                    pos_end: Some(7),
-                    message: String::from("Command '{RED}' cannot have a position reference."),
-                    suggestion: Some(String::from("Remove '2:'.")),
+                    pos_end: Some(5),
+                    message: String::from("No gender definitions allowed."),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
                 }
             );
             assert_eq!(
                 val_trans[1],
                 ValidationError {
                     severity: Severity::Error,
-                    pos_begin: Some(7),
+                    kind: FindingKind::PluralNotAllowed,
+                    pos_begin: Some(10),
+                    pos_end: Some(15),
+                    message: String::from("No plural choices allowed."),
+                    suggestion: Some(String::from("Remove '{P ...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::GenderNotAllowed,
+                    pos_begin: Some(15),
+                    pos_end: Some(20),
+                    message: String::from("No gender choices allowed."),
+                    suggestion: Some(String::from("Remove '{G ...}'.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_gschoices() {
+        let config = LanguageConfig {
+            dialect: Dialect::GAMESCRIPT,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::GenderNotAllowed,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from("No gender definitions allowed."),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::GenderNotAllowed,
+                    pos_begin: Some(17),
+                    pos_end: Some(24),
+                    message: String::from("No gender choices allowed."),
+                    suggestion: Some(String::from("Remove '{G ...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::CaseNotAllowed,
+                    pos_begin: Some(24),
+                    pos_end: Some(34),
+                    message: String::from("No case selections allowed."),
+                    suggestion: Some(String::from("Remove '.x'.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_choices() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{G=c}{NUM}{P a b c}{G a b c}{STRING.z}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 4);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownGender,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from("Unknown gender 'c'."),
+                    suggestion: Some(String::from("Known genders are: 'a', 'b'")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::ChoiceCount,
+                    pos_begin: Some(10),
                     pos_end: Some(19),
+                    message: String::from("Expected 2 plural choices, found 3."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::ChoiceCount,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from("Expected 2 gender choices, found 3."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[3],
+                ValidationError {
+                    severity: Severity::Error,
+                    kind: FindingKind::UnknownCase,
+                    pos_begin: Some(28),
+                    pos_end: Some(38),
                     message: String::from("Unknown case 'z'."),
                     suggestion: Some(String::from("Known cases are: 'x', 'y'")),
                 }
             );
+        }
+    }
+
+    #[test]
+    fn test_validate_nonpositional() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{RED}{NBSP}{}{GREEN}{NBSP}{}{RED}{TRAIN}").unwrap();
+        let val_base = validate_string(&config, &COMMANDS, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{RED}{}{GREEN}{}{RED}{TRAIN}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{}{GREEN}{NBSP}{RED}{NBSP}{GREEN}{}{RED}{TRAIN}")
+                .unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans =
+                ParsedString::parse("{RED}{}{RED}{TRAIN}{BLUE}{TRAIN}{RIGHT_ARROW}{SHIP}").unwrap();
+            let val_trans = validate_string(&config, &COMMANDS, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 4);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{GREEN}' is missing."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::CommandCount,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from(
+                        "String command '{TRAIN}': expected 1 times, found 2 times."
+                    ),
+                    suggestion: None,
+                }
+            );
             assert_eq!(
                 val_trans[2],
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("No case selection allowed for '{NUM}'."),
-                    suggestion: Some(String::from("Remove '.x'.")),
+                    severity: Severity::Warning,
+                    kind: FindingKind::UnexpectedCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{BLUE}' is unexpected."),
+                    suggestion: Some(String::from("Remove this command.")),
+                }
+            );
+            assert_eq!(
+                val_trans[3],
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::UnexpectedCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{SHIP}' is unexpected."),
+                    suggestion: Some(String::from("Remove this command.")),
                 }
             );
         }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G i j}{P i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_quick_param_check() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let cases = [
+            ("{NUM} trains", "{NUM} Züge", true),
+            ("{NUM} trains", "{COMMA} Züge", false),
+            ("{NUM} trains", "Züge", false),
+            ("{STRING} has {NUM}", "{1:NUM} bei {0:STRING}", true),
+            ("{STRING} has {NUM}", "{1:STRING} bei {0:NUM}", false),
+            ("{RED}{CARGO_LONG}", "{CARGO_LONG}{BLUE}", true),
+        ];
+        for (base, trans, expected) in cases {
+            assert_eq!(quick_param_check(base, trans), expected);
+            let full = validate_translation(&config, base, "default", trans);
+            let ok = !full.errors.iter().any(|e| e.severity == Severity::Error);
+            assert_eq!(ok, expected);
         }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 0 i j}{P 1 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
+    }
+
+    #[test]
+    fn test_suggest_fixes() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let base = ParsedString::parse("{RED}Owner: {STRING}").unwrap();
+
+        let trans = ParsedString::parse("{RED}Eigentümer: {STRING}").unwrap();
+        assert_eq!(suggest_fixes(&trans, &base, &config), vec![]);
+
+        let trans = ParsedString::parse("{RED}Eigentümer: {STRING}  ").unwrap();
+        assert_eq!(
+            suggest_fixes(&trans, &base, &config),
+            vec![Fix {
+                pos_begin: 25,
+                pos_end: 27,
+                old: String::from("  "),
+                new: String::from(""),
+            }]
+        );
+
+        let trans = ParsedString::parse("{RED}{RED}Eigentümer: {STRING.Gen}").unwrap();
+        assert_eq!(
+            suggest_fixes(&trans, &base, &config),
+            vec![
+                Fix {
+                    pos_begin: 5,
+                    pos_end: 10,
+                    old: String::from("{RED}"),
+                    new: String::from(""),
+                },
+                Fix {
+                    pos_begin: 22,
+                    pos_end: 34,
+                    old: String::from("{STRING.Gen}"),
+                    new: String::from("{STRING.gen}"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_dialects() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let parsed = ParsedString::parse("{RED}{RAW_STRING}").unwrap();
+        assert_eq!(
+            validate_dialects(
+                &config,
+                &parsed,
+                None,
+                &[Dialect::OPENTTD, Dialect::NEWGRF, Dialect::GAMESCRIPT]
+            ),
+            vec![(
+                Dialect::NEWGRF,
                 ValidationError {
                     severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(19),
+                    kind: FindingKind::UnknownCommand,
+                    pos_begin: Some(5),
+                    pos_end: Some(17),
+                    message: String::from("Unknown string command '{RAW_STRING}'."),
+                    suggestion: None,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_validate_end_colour() {
+        let base = ParsedString::parse("text").unwrap();
+
+        let trans = ParsedString::parse("{RED}text").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::EndColour,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from(
+                    "String ends with colour '{RED}', but base ends with the default colour."
+                ),
+                suggestion: Some(String::from("Add '{BLACK}' at the end.")),
+            }]
+        );
+
+        let trans = ParsedString::parse("{RED}text{BLACK}").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(val_trans, vec![]);
+
+        let trans = ParsedString::parse("{PUSH_COLOUR}{RED}text{POP_COLOUR}").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(val_trans, vec![]);
+
+        let trans = ParsedString::parse("{PUSH_COLOUR}{RED}text").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::EndColour,
+                pos_begin: Some(13),
+                pos_end: Some(18),
+                message: String::from(
+                    "String ends with colour '{RED}', but base ends with the default colour."
+                ),
+                suggestion: Some(String::from("Add '{BLACK}' at the end.")),
+            }]
+        );
+
+        let base = ParsedString::parse("{RED}text{BLACK}").unwrap();
+        let trans = ParsedString::parse("{RED}text").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(val_trans.len(), 1);
+
+        let base = ParsedString::parse("{RED}text").unwrap();
+        let trans = ParsedString::parse("{BLUE}text").unwrap();
+        let val_trans = validate_end_colour(&COMMANDS, &Dialect::OPENTTD, &trans, &base);
+        assert_eq!(val_trans, vec![]);
+    }
+
+    #[test]
+    fn test_validate_repeated_style() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_base = validate_base(&config, "{RED}{RED}x");
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::RepeatedCommand,
+                pos_begin: Some(5),
+                pos_end: Some(10),
+                message: String::from("String command '{RED}' repeats the previous command."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+
+        let newgrf = LanguageConfig {
+            dialect: Dialect::NEWGRF,
+            ..config.clone()
+        };
+        let val_base = validate_base(&newgrf, "{TINY_FONT}{TINYFONT}x");
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::RepeatedCommand,
+                pos_begin: Some(11),
+                pos_end: Some(21),
+                message: String::from("String command '{TINY_FONT}' repeats the previous command."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+
+        let val_base = validate_base(&config, "{RED}x{RED}");
+        assert_eq!(val_base.errors, vec![]);
+
+        let val_base = validate_base(&config, "{RED}{BLUE}x");
+        assert_eq!(val_base.errors, vec![]);
+
+        let val_base = validate_base(
+            &config,
+            "{PUSH_COLOUR}{PUSH_COLOUR}x{POP_COLOUR}{POP_COLOUR}",
+        );
+        assert_eq!(val_base.errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_verbatim() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let result = validate_base(&config, "{RED}Visit {RAW {P a{ b}}\t{FOOBAR}  }");
+        assert_eq!(result.errors, vec![]);
+        assert_eq!(
+            result.normalized,
+            Some(String::from("{RED}Visit {RAW {P a{ b}}\t{FOOBAR}  }"))
+        );
+    }
+
+    #[test]
+    fn test_validate_required_prefix() {
+        let parsed = ParsedString::parse("{G=n}{BLACK}{TINY_FONT}Text").unwrap();
+        assert_eq!(validate_required_prefix(&parsed, &[]), vec![]);
+        assert_eq!(validate_required_prefix(&parsed, &["BLACK"]), vec![]);
+        assert_eq!(
+            validate_required_prefix(&parsed, &["BLACK", "TINY_FONT"]),
+            vec![]
+        );
+
+        let parsed = ParsedString::parse("Text{BLACK}").unwrap();
+        assert_eq!(
+            validate_required_prefix(&parsed, &["BLACK"]),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::RequiredPrefix,
+                pos_begin: Some(0),
+                pos_end: Some(4),
+                message: String::from("String must start with '{BLACK}'."),
+                suggestion: Some(String::from("Add '{BLACK}'.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("{BLACK}").unwrap();
+        assert_eq!(
+            validate_required_prefix(&parsed, &["BLACK", "TINY_FONT"]),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::RequiredPrefix,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String must start with '{BLACK}{TINY_FONT}'."),
+                suggestion: Some(String::from("Add '{TINY_FONT}'.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_untranslated() {
+        let base = ParsedString::parse("{RED}Hello {NUM} world").unwrap();
+        let trans = ParsedString::parse("{RED}Hello {NUM} world").unwrap();
+        assert_eq!(
+            validate_untranslated(&trans, &base),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::Untranslated,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Translation is identical to the base language."),
+                suggestion: Some(String::from("Translate the text.")),
+            }]
+        );
+
+        let trans = ParsedString::parse("{RED}Hallo {NUM} Welt").unwrap();
+        assert_eq!(validate_untranslated(&trans, &base), vec![]);
+
+        let base = ParsedString::parse("{NUM} / {COMMA}").unwrap();
+        let trans = ParsedString::parse("{NUM} / {COMMA}").unwrap();
+        assert_eq!(validate_untranslated(&trans, &base), vec![]);
+    }
+
+    #[test]
+    fn test_validate_duplicate_genders() {
+        let parsed = ParsedString::parse("{STRING} is {G 0 same same}").unwrap();
+        assert_eq!(
+            validate_duplicate_genders(&parsed),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::DuplicateGenderChoice,
+                pos_begin: Some(22),
+                pos_end: Some(26),
+                message: String::from("Gender choice 2 is identical to choice 1: 'same'."),
+                suggestion: None,
+            }]
+        );
+
+        let parsed = ParsedString::parse("{G a a a}").unwrap();
+        let result = validate_duplicate_genders(&parsed);
+        assert_eq!(result.len(), 2);
+        assert_eq!((result[0].pos_begin, result[0].pos_end), (Some(5), Some(6)));
+        assert_eq!((result[1].pos_begin, result[1].pos_end), (Some(7), Some(8)));
+
+        let parsed = ParsedString::parse("{G \"a\" \"a\"}").unwrap();
+        let result = validate_duplicate_genders(&parsed);
+        assert_eq!((result[0].pos_begin, result[0].pos_end), (Some(8), Some(9)));
+
+        let parsed = ParsedString::parse("{G a b a} {P a a}").unwrap();
+        assert_eq!(validate_duplicate_genders(&parsed), vec![]);
+    }
+
+    #[test]
+    fn test_validate_with_commands() {
+        assert!(COMMANDS.is_known(&Dialect::NEWGRF, "TINYFONT"));
+        assert!(!COMMANDS.is_known(&Dialect::OPENTTD, "TINYFONT"));
+        assert_eq!(COMMANDS.arity(&Dialect::OPENTTD, "CARGO_LONG"), 2);
+        assert_eq!(
+            COMMANDS.param_type(&Dialect::OPENTTD, "CURRCOMPACT"),
+            Some(ParamKind::CURRENCY)
+        );
+        assert_eq!(COMMANDS.param_type(&Dialect::OPENTTD, "RED"), None);
+
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        struct Custom;
+        impl CommandMeta for Custom {
+            fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+                match name {
+                    "MY_PARAM" => CommandMeta::get(COMMANDS, dialect, "NUM"),
+                    "MY_COLOUR" => CommandMeta::get(COMMANDS, dialect, "RED"),
+                    _ => None,
+                }
+            }
+        }
+        let val_base = validate_base_with_commands(
+            &config,
+            &Custom,
+            "{MY_COLOUR}{MY_PARAM} {1:MY_COLOUR}{RED}",
+        );
+        assert_eq!(val_base.errors.len(), 2);
+        assert_eq!(
+            val_base.errors[0].message,
+            "Command '{MY_COLOUR}' cannot have a position reference."
+        );
+        assert_eq!(
+            val_base.errors[1].message,
+            "Unknown string command '{RED}'."
+        );
+
+        let val_base = validate_base_with_commands(&config, &Custom, "{MY_COLOUR}{MY_PARAM} items");
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized.unwrap(),
+            "{MY_COLOUR}{0:MY_PARAM} items"
+        );
+
+        let val_trans = validate_translation_with_commands(
+            &config,
+            &Custom,
+            "{MY_PARAM} items",
+            "default",
+            "{MY_PARAM.gen} stuks",
+        );
+        assert_eq!(val_trans.errors.len(), 1);
+        assert_eq!(
+            val_trans.errors[0].message,
+            "No case selection allowed for '{MY_PARAM}'."
+        );
+
+        let mut custom = HashMap::new();
+        custom.insert(
+            String::from("NUM_SHARES"),
+            CommandInfo {
+                name: "NUM_SHARES",
+                norm_name: None,
+                dialects: &[Dialect::OPENTTD],
+                category: Category::PARAMETER,
+                occurence: Occurence::EXACT,
+                allow_case: false,
+                def_plural_subindex: None,
+                parameters: CommandMeta::get(COMMANDS, &Dialect::OPENTTD, "NUM")
+                    .unwrap()
+                    .parameters,
+            },
+        );
+        assert_eq!(
+            custom.param_type(&Dialect::OPENTTD, "NUM_SHARES"),
+            Some(ParamKind::NUMERIC)
+        );
+        assert!(!custom.is_known(&Dialect::NEWGRF, "NUM_SHARES"));
+        let val_trans = validate_translation_with_commands(
+            &config,
+            &custom,
+            "{NUM_SHARES} shares",
+            "default",
+            "{NUM_SHARES} aandelen {P \"\" s}{NUM}",
+        );
+        assert_eq!(val_trans.errors.len(), 1);
+        assert_eq!(
+            val_trans.errors[0].message,
+            "Unknown string command '{NUM}'."
+        );
+    }
+
+    #[test]
+    fn test_validate_boundary_commands() {
+        let base = ParsedString::parse("{BLACK}Cost: {CURRENCY_LONG}").unwrap();
+
+        let trans = ParsedString::parse("{G=n}{BLACK}Kosten: {CURRENCY_LONG}").unwrap();
+        assert_eq!(validate_boundary_commands(&trans, &base), vec![]);
+
+        let trans = ParsedString::parse("Kosten: {CURRENCY_LONG}").unwrap();
+        assert_eq!(
+            validate_boundary_commands(&trans, &base),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::BoundaryCommands,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "String starts with no commands, but base starts with '{BLACK}'."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        let trans = ParsedString::parse("{BLACK}{CURRENCY_LONG} kosten").unwrap();
+        assert_eq!(
+            validate_boundary_commands(&trans, &base),
+            vec![
+                ValidationError {
+                    severity: Severity::Warning,
+                    kind: FindingKind::BoundaryCommands,
+                    pos_begin: Some(0),
+                    pos_end: Some(22),
                     message: String::from(
-                        "'{G}' references position '0:0', but '{0:NUM}' does not allow genders."
+                        "String starts with '{BLACK}{CURRENCY_LONG}', but base starts with '{BLACK}'."
                     ),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
+                },
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
+                    severity: Severity::Warning,
+                    kind: FindingKind::BoundaryCommands,
+                    pos_begin: None,
+                    pos_end: None,
                     message: String::from(
-                        "'{P}' references position '1:0', but '{1:STRING}' does not allow plurals."
+                        "String ends with no commands, but base ends with '{CURRENCY_LONG}'."
                     ),
                     suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 1:1 i j}{P 1:3 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_max_occurrences() {
+        struct OneCurrency;
+        impl CommandMeta for OneCurrency {
+            fn get(&self, dialect: &Dialect, name: &str) -> Option<&CommandInfo<'_>> {
+                CommandMeta::get(COMMANDS, dialect, name)
+            }
+            fn max_occurrences(&self, name: &str) -> Option<usize> {
+                (name == "CURRENCY").then_some(1)
+            }
         }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 1:4 i j}{P 1:4 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
+
+        let parsed = ParsedString::parse("{CURRENCY} of {NUM} for {CURRENCY}").unwrap();
+        assert_eq!(
+            validate_max_occurrences(&parsed, &OneCurrency),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::MaxOccurrences,
+                pos_begin: Some(24),
+                pos_end: Some(34),
+                message: String::from("Too many '{CURRENCY}' commands, at most 1 allowed."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+        assert_eq!(validate_max_occurrences(&parsed, COMMANDS), vec![]);
+
+        let parsed = ParsedString::parse("{NUM} {NUM} {CURRENCY}").unwrap();
+        assert_eq!(validate_max_occurrences(&parsed, &OneCurrency), vec![]);
+    }
+
+    #[test]
+    fn test_validate_boundary_choices() {
+        let parsed = ParsedString::parse("{NUM} train{P \"\" s}").unwrap();
+        assert_eq!(
+            validate_boundary_choices(&parsed, Severity::Warning),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::BoundaryChoice,
+                pos_begin: Some(11),
+                pos_end: Some(19),
+                message: String::from(
+                    "Choice list '{P}' at the end of the string refers to a parameter implicitly."
+                ),
+                suggestion: Some(String::from("Add a position reference.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("{G a b} {STRING} {NUM} train{P \"\" s}").unwrap();
+        let result = validate_boundary_choices(&parsed, Severity::Error);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].severity, Severity::Error);
+        assert_eq!(
+            result[0].message,
+            "Choice list '{G}' at the start of the string refers to a parameter implicitly."
+        );
+        assert_eq!(
+            result[1].message,
+            "Choice list '{P}' at the end of the string refers to a parameter implicitly."
+        );
+
+        // choice lists without parameter are orphans, not boundary choices
+        let parsed = ParsedString::parse("Train{P a b}").unwrap();
+        assert_eq!(
+            validate_boundary_choices(&parsed, Severity::Warning),
+            vec![]
+        );
+        assert_eq!(validate_orphaned_choices(&parsed).len(), 1);
+
+        let parsed = ParsedString::parse("{G=m}{P 0 a b} {NUM}").unwrap();
+        assert_eq!(
+            validate_boundary_choices(&parsed, Severity::Warning),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_validate_alias() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let val_trans = validate_translation(
+            &config,
+            "{CURRENCY_SHORT} for {CARGO_SHORT}",
+            "default",
+            "{CURRCOMPACT} for {1:SHORTCARGO}",
+        );
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(
+            val_trans.normalized.unwrap(),
+            "{0:CURRENCY_SHORT} for {1:CARGO_SHORT}"
+        );
+
+        let val_base = validate_base(&config, "{CARGO} cost {CURRCOMPACT}");
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized.unwrap(),
+            "{0:CARGO_LONG} cost {1:CURRENCY_SHORT}"
+        );
+    }
+
+    #[test]
+    fn test_validate_newlines() {
+        let base = ParsedString::parse("First line{}Second line").unwrap();
+
+        let trans = ParsedString::parse("Erste Zeile{}Zweite Zeile").unwrap();
+        assert_eq!(validate_newlines(&trans, &base), vec![]);
+
+        let trans = ParsedString::parse("Erste{}Zeile{}Zweite Zeile").unwrap();
+        assert_eq!(
+            validate_newlines(&trans, &base),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::LineBreaks,
+                pos_begin: Some(12),
+                pos_end: Some(14),
+                message: String::from("String has 2 line breaks, but base has 1; 1 extra."),
+                suggestion: Some(String::from("Remove this line break.")),
+            }]
+        );
+
+        let trans = ParsedString::parse("Erste Zeile\nZweite\nZeile").unwrap();
+        assert_eq!(validate_newlines(&trans, &base)[0].pos_begin, Some(18));
+
+        let trans = ParsedString::parse("Erste Zeile Zweite Zeile").unwrap();
+        assert_eq!(
+            validate_newlines(&trans, &base),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::LineBreaks,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String has 0 line breaks, but base has 1; 1 missing."),
+                suggestion: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_orphaned_choices() {
+        let parsed = ParsedString::parse("Some {P train trains}").unwrap();
+        assert_eq!(
+            validate_orphaned_choices(&parsed),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::OrphanedChoice,
+                pos_begin: Some(5),
+                pos_end: Some(21),
+                message: String::from("Choice list '{P}' does not refer to any parameter."),
+                suggestion: Some(String::from("Remove the choice list.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("{NUM} {P train trains} {G 0 a b}{STRING}").unwrap();
+        assert_eq!(validate_orphaned_choices(&parsed), vec![]);
+
+        let parsed = ParsedString::parse("{NUM} {P 1 train trains} {G a b}").unwrap();
+        assert_eq!(validate_orphaned_choices(&parsed).len(), 2);
+    }
+
+    #[test]
+    fn test_validate_case_changes() {
+        let base = ParsedString::parse("{STRING} of {STRING.gen}").unwrap();
+
+        let trans = ParsedString::parse("{1:STRING.gen} {0:STRING}").unwrap();
+        assert_eq!(validate_case_changes(&trans, &base), vec![]);
+
+        let trans = ParsedString::parse("{STRING.gen} von {STRING}").unwrap();
+        assert_eq!(
+            validate_case_changes(&trans, &base),
+            vec![
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(21),
+                    severity: Severity::Warning,
+                    kind: FindingKind::CaseChange,
+                    pos_begin: Some(0),
+                    pos_end: Some(12),
                     message: String::from(
-                        "'{G}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                        "Case '.gen' is added to position 0, but base has no case."
                     ),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
+                },
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(21),
-                    pos_end: Some(32),
+                    severity: Severity::Warning,
+                    kind: FindingKind::CaseChange,
+                    pos_begin: Some(17),
+                    pos_end: Some(25),
                     message: String::from(
-                        "'{P}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                        "Case '.gen' of position 1 is removed, compared to base."
                     ),
                     suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 2 i j}{P 2 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_string_refs() {
+        let known_ids = HashSet::from([String::from("STR_TOWN"), String::from("STR_CITY")]);
+
+        let parsed = ParsedString::parse("{STRING:STR_TOWN} and {STRING:STR_CITY}").unwrap();
+        assert_eq!(validate_string_refs(&parsed, &known_ids), vec![]);
+
+        let parsed = ParsedString::parse("{STRING:STR_TOWN} and {STRING:STR_VILLAGE}").unwrap();
+        assert_eq!(
+            validate_string_refs(&parsed, &known_ids),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::UnknownStringReference,
+                pos_begin: Some(22),
+                pos_end: Some(42),
+                message: String::from("Referenced string 'STR_VILLAGE' does not exist."),
+                suggestion: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_max_chars() {
+        let parsed = ParsedString::parse("{RED}Hello {NUM} worlds").unwrap();
+        assert_eq!(validate_max_chars(&parsed, 23, false), vec![]);
+        assert_eq!(validate_max_chars(&parsed, 13, true), vec![]);
+        assert_eq!(
+            validate_max_chars(&parsed, 20, false),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::MaxLength,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "String is 23 characters long, which is 3 more than the maximum of 20."
+                ),
+                suggestion: Some(String::from("Shorten the string.")),
+            }]
+        );
+        assert_eq!(
+            validate_max_chars(&parsed, 10, true),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::MaxLength,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "String is 13 characters long, which is 3 more than the maximum of 10."
+                ),
+                suggestion: Some(String::from("Shorten the string.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_max_glyphs() {
+        assert_eq!(glyph_count("abc"), 3);
+        assert_eq!(
+            glyph_count("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"),
+            1
+        );
+        assert_eq!(glyph_count("\u{1F44D}\u{1F3FD}!"), 2);
+        assert_eq!(glyph_count("\u{1F1F3}\u{1F1F1}\u{1F1E9}\u{1F1EA}"), 2);
+        assert_eq!(glyph_count("\u{2764}\u{FE0F}"), 1);
+
+        let parsed =
+            ParsedString::parse("{RED}Family: \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}")
+                .unwrap();
+        assert_eq!(validate_max_glyphs(&parsed, 9, true), vec![]);
+        assert_eq!(validate_max_glyphs(&parsed, 14, false), vec![]);
+        assert_eq!(validate_max_chars(&parsed, 9, true).len(), 1);
+        assert_eq!(
+            validate_max_glyphs(&parsed, 8, true),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::MaxLength,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "String is 9 glyphs long, which is 1 more than the maximum of 8."
+                ),
+                suggestion: Some(String::from("Shorten the string.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_max_commands() {
+        let parsed = ParsedString::parse("{G=m}{RED}{NUM} {P a b}{# note} text").unwrap();
+        assert_eq!(validate_max_commands(&parsed, 4), vec![]);
+        assert_eq!(
+            validate_max_commands(&parsed, 3),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::MaxCommands,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "String has 4 commands, which is more than the maximum of 3."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        let parsed = ParsedString::parse(&"{NBSP}".repeat(300)).unwrap();
+        assert_eq!(validate_max_commands(&parsed, 256).len(), 1);
+    }
+
+    #[test]
+    fn test_validate_tabs() {
+        let parsed = ParsedString::parse("{RED}Name:\t{STRING}\tx").unwrap();
+        assert_eq!(
+            validate_tabs(&parsed, Severity::Warning),
+            vec![
                 ValidationError {
-                    severity: Severity::Error,
+                    severity: Severity::Warning,
+                    kind: FindingKind::TabCharacter,
                     pos_begin: Some(10),
-                    pos_end: Some(19),
-                    message: String::from("'{G}' references position '2', which has no parameter."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
+                    pos_end: Some(11),
+                    message: String::from("Tab character found."),
+                    suggestion: Some(String::from("Replace the tab with a space.")),
+                },
                 ValidationError {
-                    severity: Severity::Error,
+                    severity: Severity::Warning,
+                    kind: FindingKind::TabCharacter,
                     pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("'{P}' references position '2', which has no parameter."),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{P i j}{NUM}{STRING.y}{G i j}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(5),
-                    pos_end: Some(12),
-                    message: String::from(
-                        "'{P}' references position '-1', which has no parameter."
-                    ),
-                    suggestion: Some(String::from("Add a position reference.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(27),
-                    pos_end: Some(34),
-                    message: String::from("'{G}' references position '2', which has no parameter."),
-                    suggestion: Some(String::from("Add a position reference.")),
-                }
+                    pos_end: Some(20),
+                    message: String::from("Tab character found."),
+                    suggestion: Some(String::from("Replace the tab with a space.")),
+                },
+            ]
+        );
+        assert_eq!(
+            validate_tabs(&parsed, Severity::Error)[0].severity,
+            Severity::Error
+        );
+
+        let parsed = ParsedString::parse("{RAW \t}Name: {STRING}").unwrap();
+        assert_eq!(validate_tabs(&parsed, Severity::Error), vec![]);
+    }
+
+    #[test]
+    fn test_validate_empty_command() {
+        let parsed = ParsedString::parse("Line{}Next {NUM}").unwrap();
+        assert_eq!(validate_empty_command(&parsed, true), vec![]);
+        assert_eq!(
+            validate_empty_command(&parsed, false),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::EmptyCommand,
+                pos_begin: Some(4),
+                pos_end: Some(6),
+                message: String::from("String command '{}' is not allowed in this string."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("Text {{} {NUM}").unwrap();
+        assert_eq!(validate_empty_command(&parsed, false), vec![]);
+    }
+
+    #[test]
+    fn test_validator() {
+        let validator = Validator::new()
+            .with_check(|parsed| validate_max_chars(parsed, 5, true))
+            .with_check(|parsed| validate_invisible_chars(parsed, INVISIBLE_CHARS))
+            .with_check(|parsed| validate_required_prefix(parsed, &["BLACK"]));
+
+        let parsed = ParsedString::parse("{BLACK}Text").unwrap();
+        assert_eq!(validator.run(&parsed, RunMode::All), vec![]);
+        assert_eq!(validator.run(&parsed, RunMode::FailFast), vec![]);
+
+        let parsed = ParsedString::parse("Long\u{200B}\u{200B} text").unwrap();
+        let all = validator.run(&parsed, RunMode::All);
+        assert_eq!(all.len(), 4);
+        let first = validator.run(&parsed, RunMode::FailFast);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0], all[0]);
+    }
+
+    #[test]
+    fn test_validator_noqa() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+
+        let val_trans = validate_translation(&config, "{NUM} trains", "default", "{NUM} {FOO}");
+        assert_eq!(val_trans.errors.len(), 1);
+        assert_eq!(val_trans.errors[0].kind, FindingKind::UnknownCommand);
+        assert_eq!(val_trans.normalized, None);
+
+        let val_trans = validate_translation(
+            &config,
+            "{NUM} trains",
+            "default",
+            "{#noqa:unknown-command}{NUM} {FOO}",
+        );
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(val_trans.normalized, Some(String::from("{0:NUM} {FOO}")));
+
+        let val_trans = validate_translation(
+            &config,
+            "{NUM} trains",
+            "default",
+            "{#noqa:tab-character}{NUM} {FOO}",
+        );
+        assert_eq!(val_trans.errors.len(), 1);
+
+        let val_base = validate_base(&config, "{#noqa: repeated-command, foo}{RED}{RED}x");
+        assert_eq!(val_base.errors, vec![]);
+
+        let validator = Validator::new()
+            .with_check(move |parsed| validate_string(&config, &COMMANDS, parsed, None))
+            .with_check(|parsed| validate_tabs(parsed, Severity::Warning));
+
+        let parsed = ParsedString::parse("{FOO}\ttext").unwrap();
+        assert_eq!(validator.run(&parsed, RunMode::All).len(), 2);
+
+        let parsed = ParsedString::parse("{#noqa:unknown-command}{FOO}\ttext").unwrap();
+        let result = validator.run(&parsed, RunMode::All);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].kind, FindingKind::TabCharacter);
+
+        let parsed =
+            ParsedString::parse("{#noqa: tab-character, unknown-command}{FOO}\ttext").unwrap();
+        assert_eq!(validator.run(&parsed, RunMode::All), vec![]);
+    }
+
+    #[test]
+    fn test_severity_map() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            relaxed_genders: false,
+            severities: SeverityMap::new(),
+        };
+        let commands =
+            move |parsed: &ParsedString| validate_string(&config, &COMMANDS, parsed, None);
+        let parsed = ParsedString::parse("{FOO}\ttext").unwrap();
+        let validator = Validator::new()
+            .with_check(commands.clone())
+            .with_check(|parsed| validate_tabs(parsed, Severity::Error));
+        let result = validator.run(&parsed, RunMode::All);
+        assert_eq!(result[0].message, "Unknown string command '{FOO}'.");
+        assert_eq!(result[0].severity, Severity::Error);
+        assert_eq!(result[1].severity, Severity::Error);
+
+        let validator = Validator::new()
+            .with_check(commands)
+            .with_check(|parsed| validate_tabs(parsed, Severity::Error))
+            .with_severities(
+                SeverityMap::new().with(FindingKind::UnknownCommand, Severity::Warning),
             );
+        let result = validator.run(&parsed, RunMode::All);
+        assert_eq!(result[0].message, "Unknown string command '{FOO}'.");
+        assert_eq!(result[0].severity, Severity::Warning);
+        assert_eq!(result[1].severity, Severity::Error);
+
+        let map = SeverityMap::new()
+            .with(FindingKind::UnknownCommand, Severity::Warning)
+            .with(FindingKind::UnknownCommand, Severity::Error);
+        assert_eq!(map.severity(&result[0]), Severity::Error);
+
+        let mut config: LanguageConfig = serde_json::from_str(
+            r#"{"dialect": "openttd", "cases": [], "genders": [], "plural_count": 2,
+                "severities": {"unknown-command": "warning"}}"#,
+        )
+        .unwrap();
+        let val_trans = validate_translation(&config, "{NUM} trains", "default", "{NUM} {FOO}");
+        assert_eq!(val_trans.errors.len(), 1);
+        assert_eq!(val_trans.errors[0].kind, FindingKind::UnknownCommand);
+        assert_eq!(val_trans.errors[0].severity, Severity::Warning);
+        assert_eq!(val_trans.normalized, Some(String::from("{0:NUM} {FOO}")));
+
+        config.severities = SeverityMap::new();
+        let val_base = validate_base(&config, "{FOO}");
+        assert_eq!(val_base.errors[0].severity, Severity::Error);
+        assert_eq!(val_base.normalized, None);
+
+        let invalid: Result<LanguageConfig, _> = serde_json::from_str(
+            r#"{"dialect": "openttd", "cases": [], "genders": [], "plural_count": 2,
+                "severities": {"unknown": "warning"}}"#,
+        );
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_finding_kind() {
+        for kind in FindingKind::ALL {
+            assert_eq!(FindingKind::try_from(kind.as_str()), Ok(*kind));
         }
+        let ids: HashSet<&str> = FindingKind::ALL.iter().map(|k| k.as_str()).collect();
+        assert_eq!(ids.len(), FindingKind::ALL.len());
+        assert!(FindingKind::try_from("foo").is_err());
+
+        let result = validate_base(&LanguageConfig::for_language("en_GB").unwrap(), "{FOO}");
+        assert_eq!(result.errors[0].kind, FindingKind::UnknownCommand);
+        let json = serde_json::to_value(&result.errors[0]).unwrap();
+        assert_eq!(json["kind"], "unknown-command");
+    }
+
+    #[test]
+    fn test_dedup_findings() {
+        let finding = |kind: FindingKind,
+                       pos_begin: Option<usize>,
+                       pos_end: Option<usize>,
+                       message: &str| ValidationError {
+            severity: Severity::Error,
+            kind,
+            pos_begin,
+            pos_end,
+            message: String::from(message),
+            suggestion: None,
+        };
+        let unknown = FindingKind::UnknownCommand;
+        let choice = FindingKind::InvalidChoiceReference;
+        let length = FindingKind::MaxLength;
+
+        assert_eq!(
+            dedup_findings(vec![
+                finding(unknown, Some(0), Some(5), "Unknown string command '{FOO}'."),
+                finding(unknown, Some(0), Some(5), "Unknown string command '{FOO}'."),
+            ]),
+            vec![finding(
+                unknown,
+                Some(0),
+                Some(5),
+                "Unknown string command '{FOO}'."
+            )]
+        );
+
+        assert_eq!(
+            dedup_findings(vec![
+                finding(choice, Some(0), Some(10), "Choice list is broken."),
+                finding(length, None, None, "String is too long."),
+                finding(choice, Some(2), Some(6), "Choice list has no parameter."),
+                finding(choice, Some(10), Some(15), "Choice list is broken."),
+                finding(length, None, None, "String is too long."),
+                finding(length, None, None, "String has too many glyphs."),
+                finding(unknown, Some(2), Some(6), "Other problem."),
+            ]),
+            vec![
+                finding(choice, Some(2), Some(6), "Choice list has no parameter."),
+                finding(length, None, None, "String is too long."),
+                finding(choice, Some(10), Some(15), "Choice list is broken."),
+                finding(length, None, None, "String has too many glyphs."),
+                finding(unknown, Some(2), Some(6), "Other problem."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_colours() {
+        let parsed = ParsedString::parse("{RED}Error:{TINY_FONT} {STRING}").unwrap();
+        assert_eq!(validate_colours(&parsed, true), vec![]);
+        assert_eq!(
+            validate_colours(&parsed, false),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourNotAllowed,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from("Colour command '{RED}' is not allowed in this string."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+
+        let parsed = ParsedString::parse("{PUSH_COLOUR}{1:COLOUR}x{POP_COLOUR}").unwrap();
+        assert_eq!(validate_colours(&parsed, false).len(), 3);
+    }
+
+    #[test]
+    fn test_validate_colour_order() {
+        let base = ParsedString::parse("{RED}Error:{WHITE} {STRING}").unwrap();
+
+        let test = ParsedString::parse("{RED}Fout:{WHITE} {STRING}").unwrap();
+        assert_eq!(validate_colour_order(&test, &base), vec![]);
+
+        let test = ParsedString::parse("{WHITE}Fout:{RED} {STRING}").unwrap();
+        assert_eq!(
+            validate_colour_order(&test, &base),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: Some(0),
+                pos_end: Some(7),
+                message: String::from("Colour '{WHITE}' is used where base uses '{RED}'."),
+                suggestion: Some(String::from("Use '{RED}' instead.")),
+            }]
+        );
+
+        let test = ParsedString::parse("{RED}Fout: {STRING}").unwrap();
+        assert_eq!(
+            validate_colour_order(&test, &base),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Colour '{WHITE}' of base is missing."),
+                suggestion: Some(String::from("Add '{WHITE}'.")),
+            }]
+        );
+
+        let test = ParsedString::parse("{RED}Fout:{WHITE} {STRING}{RED}").unwrap();
+        assert_eq!(
+            validate_colour_order(&test, &base),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::ColourOrder,
+                pos_begin: Some(26),
+                pos_end: Some(31),
+                message: String::from("Colour '{RED}' is not used at this point in base."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
     }
 
     #[test]
-    fn test_validate_nochoices() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 1,
-        };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+    fn test_validate_long_choice_items() {
+        let parsed =
+            ParsedString::parse("{NUM} {P car cars \"cars, see also the next page\"}").unwrap();
+        assert_eq!(
+            validate_long_choice_items(&parsed, 5.0),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::LongChoiceItem,
+                pos_begin: Some(19),
+                pos_end: Some(47),
+                message: String::from(
+                    "Choice item 'cars, see also the next page' is much longer than the other items of '{P}'."
+                ),
+                suggestion: Some(String::from("Check whether the text belongs in this item.")),
+            }]
+        );
+        assert_eq!(validate_long_choice_items(&parsed, 10.0), vec![]);
 
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a}{G a}{STRING}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("No gender definitions allowed."),
-                    suggestion: Some(String::from("Remove '{G=...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(15),
-                    message: String::from("No plural choices allowed."),
-                    suggestion: Some(String::from("Remove '{P ...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(15),
-                    pos_end: Some(20),
-                    message: String::from("No gender choices allowed."),
-                    suggestion: Some(String::from("Remove '{G ...}'.")),
-                }
-            );
-        }
+        let parsed = ParsedString::parse("{NUM} {P \"a\" bbbbbbbbbbbbbbbbbb}").unwrap();
+        let result = validate_long_choice_items(&parsed, 5.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            (result[0].pos_begin, result[0].pos_end),
+            (Some(13), Some(31))
+        );
+
+        let parsed = ParsedString::parse("{NUM} {P ab abcd abc}").unwrap();
+        assert_eq!(validate_long_choice_items(&parsed, 1.5), vec![]);
+
+        let parsed = ParsedString::parse("{NUM} {P \"\" a}").unwrap();
+        assert_eq!(validate_long_choice_items(&parsed, 1.0), vec![]);
     }
 
     #[test]
-    fn test_validate_gschoices() {
-        let config = LanguageConfig {
-            dialect: Dialect::GAMESCRIPT,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
-            plural_count: 2,
-        };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
-
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("No gender definitions allowed."),
-                    suggestion: Some(String::from("Remove '{G=...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
+    fn test_validate_format_specifiers() {
+        let parsed = ParsedString::parse("{RED}Über %s kosten {NUM}%1$d").unwrap();
+        assert_eq!(
+            validate_format_specifiers(&parsed),
+            vec![
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(17),
-                    pos_end: Some(24),
-                    message: String::from("No gender choices allowed."),
-                    suggestion: Some(String::from("Remove '{G ...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[2],
+                    severity: Severity::Warning,
+                    kind: FindingKind::FormatSpecifier,
+                    pos_begin: Some(10),
+                    pos_end: Some(12),
+                    message: String::from("Format specifier '%s' found in text."),
+                    suggestion: Some(String::from("Use a string command instead.")),
+                },
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(24),
-                    pos_end: Some(34),
-                    message: String::from("No case selections allowed."),
-                    suggestion: Some(String::from("Remove '.x'.")),
-                }
-            );
-        }
+                    severity: Severity::Warning,
+                    kind: FindingKind::FormatSpecifier,
+                    pos_begin: Some(25),
+                    pos_end: Some(29),
+                    message: String::from("Format specifier '%1$d' found in text."),
+                    suggestion: Some(String::from("Use a string command instead.")),
+                },
+            ]
+        );
+
+        let parsed = ParsedString::parse("{NUM}% discount, 100% sure, {NUM}%stock").unwrap();
+        assert_eq!(validate_format_specifiers(&parsed), vec![]);
     }
 
     #[test]
-    fn test_validate_choices() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
-            plural_count: 2,
-        };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+    fn test_validate_inferred_kinds() {
+        let base = ParsedString::parse("{NUM} {STRING} for {CURRENCY_LONG}").unwrap();
 
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{G=c}{NUM}{P a b c}{G a b c}{STRING.z}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 4);
-            assert_eq!(
-                val_trans[0],
+        let test = ParsedString::parse("{COMMA} {RAW_STRING} voor {CURRENCY_SHORT}").unwrap();
+        assert_eq!(validate_inferred_kinds(&test, &base), vec![]);
+
+        let test = ParsedString::parse("{1:NUM} {0:STRING} voor {2:CURRENCY_LONG}").unwrap();
+        assert_eq!(
+            validate_inferred_kinds(&test, &base),
+            vec![
                 ValidationError {
                     severity: Severity::Error,
+                    kind: FindingKind::ParameterKind,
                     pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("Unknown gender 'c'."),
-                    suggestion: Some(String::from("Known genders are: 'a', 'b'")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(19),
-                    message: String::from("Expected 2 plural choices, found 3."),
+                    pos_end: Some(7),
+                    message: String::from(
+                        "Position 1 looks like a NUMERIC parameter, but base uses a STRING parameter."
+                    ),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[2],
+                },
                 ValidationError {
                     severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("Expected 2 gender choices, found 3."),
+                    kind: FindingKind::ParameterKind,
+                    pos_begin: Some(8),
+                    pos_end: Some(18),
+                    message: String::from(
+                        "Position 0 looks like a STRING parameter, but base uses a NUMERIC parameter."
+                    ),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[3],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(28),
-                    pos_end: Some(38),
-                    message: String::from("Unknown case 'z'."),
-                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
-                }
-            );
-        }
+                },
+            ]
+        );
+
+        let test = ParsedString::parse("{MY_NUMBER} {STRING} voor {CURRENCY_LONG}").unwrap();
+        assert_eq!(validate_inferred_kinds(&test, &base).len(), 2);
     }
 
     #[test]
-    fn test_validate_nonpositional() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 0,
-        };
-        let base = ParsedString::parse("{RED}{NBSP}{}{GREEN}{NBSP}{}{RED}{TRAIN}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+    fn test_validate_unused_parameters() {
+        let base = ParsedString::parse("{NUM} {STRING} from {STATION}").unwrap();
 
-        {
-            let trans = ParsedString::parse("{RED}{}{GREEN}{}{RED}{TRAIN}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{RED}{}{GREEN}{NBSP}{RED}{NBSP}{GREEN}{}{RED}{TRAIN}")
-                .unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans =
-                ParsedString::parse("{RED}{}{RED}{TRAIN}{BLUE}{TRAIN}{RIGHT_ARROW}{SHIP}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 4);
-            assert_eq!(
-                val_trans[0],
+        let test = ParsedString::parse("{0:NUM} van {2:STATION}").unwrap();
+        assert_eq!(
+            validate_unused_parameters(&test, &base),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::UnusedParameter,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "Parameter 1 of base is not used, its value will not be shown."
+                ),
+                suggestion: Some(String::from("Add '{1:STRING}'.")),
+            }]
+        );
+
+        let test = ParsedString::parse("{2:STATION}: {0:NUM} {1:STRING}").unwrap();
+        assert_eq!(validate_unused_parameters(&test, &base), vec![]);
+    }
+
+    #[test]
+    fn test_validate_shared_choice_refs() {
+        let parsed = ParsedString::parse("{NUM}{P 0 a b}{G 0 m f}").unwrap();
+        let message = "Plural list '{P}' and gender list '{G}' both refer to position 0.";
+        assert_eq!(
+            validate_shared_choice_refs(&parsed),
+            vec![
                 ValidationError {
                     severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{GREEN}' is missing."),
+                    kind: FindingKind::SharedChoiceReference,
+                    pos_begin: Some(5),
+                    pos_end: Some(14),
+                    message: String::from(message),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
+                },
                 ValidationError {
                     severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from(
-                        "String command '{TRAIN}': expected 1 times, found 2 times."
-                    ),
+                    kind: FindingKind::SharedChoiceReference,
+                    pos_begin: Some(14),
+                    pos_end: Some(23),
+                    message: String::from(message),
                     suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{BLUE}' is unexpected."),
-                    suggestion: Some(String::from("Remove this command.")),
-                }
-            );
-            assert_eq!(
-                val_trans[3],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{SHIP}' is unexpected."),
-                    suggestion: Some(String::from("Remove this command.")),
-                }
-            );
-        }
+                },
+            ]
+        );
+
+        let parsed = ParsedString::parse("{NUM} {P car cars} {G a b}{STRING}").unwrap();
+        assert_eq!(validate_shared_choice_refs(&parsed), vec![]);
+    }
+
+    #[test]
+    fn test_validate_index_shifts() {
+        let base = ParsedString::parse("{0:NUM} {1:STRING}").unwrap();
+
+        let test = ParsedString::parse("{0:NUM} {2:STRING}").unwrap();
+        assert_eq!(
+            validate_index_shifts(&test, &base),
+            vec![ValidationError {
+                severity: Severity::Error,
+                kind: FindingKind::IndexShift,
+                pos_begin: Some(8),
+                pos_end: Some(18),
+                message: String::from(
+                    "'{2:STRING}' uses position 2, but base uses position 1 for '{STRING}'."
+                ),
+                suggestion: Some(String::from("Use '{1:STRING}'.")),
+            }]
+        );
+
+        let test = ParsedString::parse("{1:STRING}: {0:NUM}").unwrap();
+        assert_eq!(validate_index_shifts(&test, &base), vec![]);
+
+        let base = ParsedString::parse("{STRING} {STRING}").unwrap();
+        let test = ParsedString::parse("{1:STRING} {0:STRING}").unwrap();
+        assert_eq!(validate_index_shifts(&test, &base), vec![]);
+    }
+
+    #[test]
+    fn test_validate_invisible_chars() {
+        let parsed = ParsedString::parse("{RED}Zero\u{200B}width {NUM}").unwrap();
+        assert_eq!(
+            validate_invisible_chars(&parsed, INVISIBLE_CHARS),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                kind: FindingKind::InvisibleCharacter,
+                pos_begin: Some(9),
+                pos_end: Some(10),
+                message: String::from("Invisible character U+200B found."),
+                suggestion: Some(String::from("Remove this character.")),
+            }]
+        );
+        assert_eq!(validate_invisible_chars(&parsed, &['\u{FEFF}']), vec![]);
+
+        let parsed = ParsedString::parse("\u{FEFF}Text").unwrap();
+        assert_eq!(validate_invisible_chars(&parsed, INVISIBLE_CHARS).len(), 1);
+
+        let parsed = ParsedString::parse("Text").unwrap();
+        assert_eq!(validate_invisible_chars(&parsed, INVISIBLE_CHARS), vec![]);
+
+        let parsed =
+            ParsedString::parse("\u{645}\u{6CC}\u{200C}\u{62E}\u{648}\u{627}\u{647}\u{645}")
+                .unwrap();
+        assert_eq!(validate_invisible_chars(&parsed, INVISIBLE_CHARS), vec![]);
+        assert_eq!(validate_invisible_chars(&parsed, &['\u{200C}']).len(), 1);
     }
 
     #[test]
     fn test_normalize_cmd() {
         let mut parsed =
             ParsedString::parse("{RED}{NBSP}{2:RAW_STRING}{0:STRING5}{COMMA}").unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&COMMANDS, &Dialect::OPENTTD, &mut parsed);
         let result = parsed.compile();
         assert_eq!(result, "{RED}{NBSP}{2:STRING}{0:STRING}{1:COMMA}");
     }
@@ -1586,7 +5477,7 @@ mod tests {
     #[test]
     fn test_normalize_ref() {
         let mut parsed = ParsedString::parse("{RED}{NBSP}{P a b}{2:STRING}{P 1 a b}{G 0:1 a b}{0:STRING}{G 0 a b}{P 0:1 a b}{COMMA}{P a b}{G a b}").unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&COMMANDS, &Dialect::OPENTTD, &mut parsed);
         let result = parsed.compile();
         assert_eq!(result, "{RED}{NBSP}{P a b}{2:STRING}{P 1 a b}{G 0:1 a b}{0:STRING}{G 0 a b}{P 0:1 a b}{1:COMMA}{P 1 a b}{G 2 a b}");
     }
@@ -1597,7 +5488,7 @@ mod tests {
             "{NUM}{P 0:0 a b}{G 1:0 a b}{G 1:1 a b}{STRING}{P 1:2 a b}{CARGO_LONG}{P 2:1 a b}",
         )
         .unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&COMMANDS, &Dialect::OPENTTD, &mut parsed);
         let result = parsed.compile();
         assert_eq!(
             result,